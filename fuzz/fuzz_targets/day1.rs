@@ -0,0 +1,10 @@
+#![no_main]
+
+use aoc_2023::day1::{self, Mode, Vocabulary};
+use libfuzzer_sys::fuzz_target;
+
+// Day 1 has no separate parser: the calibration reads the lines directly.
+fuzz_target!(|data: &str| {
+    let _ = day1::puzzle_a(data, Mode::Strict);
+    let _ = day1::puzzle_b(data, &Vocabulary::english(), Mode::Lenient);
+});
@@ -0,0 +1,8 @@
+#![no_main]
+
+use aoc_2023::day2::CubeGame;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    let _ = data.parse::<CubeGame>();
+});
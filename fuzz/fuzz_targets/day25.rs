@@ -0,0 +1,8 @@
+#![no_main]
+
+use aoc_2023::day25;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    let _ = day25::extract_wiring_diagram(data);
+});
@@ -0,0 +1,8 @@
+#![no_main]
+
+use aoc_2023::day3::Schematic;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    let _ = Schematic::parse(data);
+});
@@ -0,0 +1,8 @@
+#![no_main]
+
+use aoc_2023::day4;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    let _ = day4::parse_scratch_cards(data);
+});
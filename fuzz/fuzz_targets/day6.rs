@@ -0,0 +1,9 @@
+#![no_main]
+
+use aoc_2023::day6;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    let _ = day6::extract_races(data);
+    let _ = day6::extract_race(data);
+});
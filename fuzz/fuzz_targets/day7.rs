@@ -0,0 +1,9 @@
+#![no_main]
+
+use aoc_2023::day7::{self, Rules};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    let _ = day7::extract_plays(data, &Rules::camel_cards());
+    let _ = day7::extract_plays(data, &Rules::camel_cards_with_jokers());
+});
@@ -0,0 +1,8 @@
+#![no_main]
+
+use aoc_2023::day9;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    let _ = day9::extract_history_rows(data);
+});
@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// The shape of the states visited by repeating a transition: after the first `prefix` steps, the
+/// same `period` states repeat forever. Every state space with finitely many states ends in a
+/// cycle, and the detectors below only return once they find it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Cycle {
+    pub prefix: u64,
+    pub period: u64,
+}
+
+impl Cycle {
+    /// The earliest step at which the state is the same as after `steps` steps.
+    pub fn earliest_equivalent_step(&self, steps: u64) -> u64 {
+        if steps < self.prefix {
+            return steps;
+        }
+
+        self.prefix + (steps - self.prefix) % self.period
+    }
+}
+
+/// Finds the cycle by remembering the step at which every state was first seen, until one comes
+/// back. Uses memory for every state before the cycle closes, but calls the transition only
+/// `prefix + period` times.
+pub fn find_cycle_hashed<S: Clone + Eq + Hash>(
+    start: S,
+    mut transition: impl FnMut(&S) -> S,
+) -> Cycle {
+    let mut first_seen: HashMap<S, u64> = HashMap::new();
+    let mut state = start;
+    let mut step = 0;
+
+    loop {
+        if let Some(&prefix) = first_seen.get(&state) {
+            return Cycle {
+                prefix,
+                period: step - prefix,
+            };
+        }
+
+        let next = transition(&state);
+        let _ = first_seen.insert(state, step);
+        state = next;
+        step += 1;
+    }
+}
+
+/// Finds the cycle with Brent's algorithm, which only keeps two states in memory at a time at
+/// the cost of calling the transition a few times more.
+pub fn find_cycle_brent<S: Clone + Eq>(start: S, mut transition: impl FnMut(&S) -> S) -> Cycle {
+    // A runner moves ahead of a tortoise that teleports to it at every power of two, until the
+    // runner meets it. The amount of steps since the last teleport is then the period.
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = start.clone();
+    let mut runner = transition(&start);
+
+    while tortoise != runner {
+        if power == period {
+            tortoise = runner.clone();
+            power *= 2;
+            period = 0;
+        }
+        runner = transition(&runner);
+        period += 1;
+    }
+
+    // With a runner a period ahead, both meet at the first state of the cycle.
+    let mut tortoise = start.clone();
+    let mut runner = start;
+    for _ in 0..period {
+        runner = transition(&runner);
+    }
+
+    let mut prefix = 0;
+    while tortoise != runner {
+        tortoise = transition(&tortoise);
+        runner = transition(&runner);
+        prefix += 1;
+    }
+
+    Cycle { prefix, period }
+}
+
+/// The state after repeating the transition `steps` times, skipping ahead once the states start
+/// to repeat. The transition is called at most `prefix + period` times.
+pub fn state_after<S: Clone + Eq + Hash>(
+    start: S,
+    mut transition: impl FnMut(&S) -> S,
+    steps: u64,
+) -> S {
+    let mut first_seen: HashMap<S, u64> = HashMap::new();
+    let mut states = vec![];
+    let mut state = start;
+
+    for step in 0..steps {
+        if let Some(&prefix) = first_seen.get(&state) {
+            let cycle = Cycle {
+                prefix,
+                period: step - prefix,
+            };
+
+            return states.swap_remove(cycle.earliest_equivalent_step(steps) as usize);
+        }
+
+        let next = transition(&state);
+        let _ = first_seen.insert(state.clone(), step);
+        states.push(state);
+        state = next;
+    }
+
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn cycles() {
+        let square_plus_one = |x: &u64| (x * x + 1) % 255;
+        let hashed = find_cycle_hashed(3, square_plus_one);
+        assert_eq!(find_cycle_brent(3, square_plus_one), hashed);
+
+        let mut states = vec![3];
+        for _ in 0..(hashed.prefix + hashed.period) {
+            states.push(square_plus_one(states.last().expect("a state")));
+        }
+        let repeat = states[(hashed.prefix + hashed.period) as usize];
+        assert_eq!(repeat, states[hashed.prefix as usize]);
+        assert_eq!(
+            states.iter().filter(|&&state| state == repeat).count(),
+            2,
+            "the cycle closes the first time a state repeats"
+        );
+
+        let counter = |x: &u64| (x + 1) % 5;
+        let expected = Cycle {
+            prefix: 0,
+            period: 5,
+        };
+        assert_eq!(find_cycle_hashed(0, counter), expected);
+        assert_eq!(find_cycle_brent(0, counter), expected);
+
+        let fixed_point = |x: &u64| x.saturating_sub(1);
+        let expected = Cycle {
+            prefix: 7,
+            period: 1,
+        };
+        assert_eq!(find_cycle_hashed(7, fixed_point), expected);
+        assert_eq!(find_cycle_brent(7, fixed_point), expected);
+    }
+
+    #[test]
+    fn skipping_ahead() {
+        // 10, 9, ..., 4, then 3, 2, 1, 0 forever.
+        let transition = |x: &i64| if *x > 0 { x - 1 } else { (x - 1).rem_euclid(4) };
+        let cycle = find_cycle_hashed(10, transition);
+        assert_eq!(
+            cycle,
+            Cycle {
+                prefix: 7,
+                period: 4
+            }
+        );
+
+        assert_eq!(cycle.earliest_equivalent_step(3), 3);
+        assert_eq!(cycle.earliest_equivalent_step(7), 7);
+        assert_eq!(cycle.earliest_equivalent_step(13), 9);
+
+        assert_eq!(state_after(10, transition, 0), 10);
+        assert_eq!(state_after(10, transition, 4), 6);
+        assert_eq!(state_after(10, transition, 1_000_000_000), 2);
+        assert_eq!(state_after(10, transition, u64::MAX), 3);
+    }
+
+    /// A random transition on the states `0..size`, and a state to start from.
+    fn transition_table() -> impl Strategy<Value = (Vec<usize>, usize)> {
+        (1..40_usize).prop_flat_map(|size| (prop::collection::vec(0..size, size), 0..size))
+    }
+
+    proptest! {
+        #[test]
+        fn detectors_agree_with_brute_force((table, start) in transition_table(), steps in 0..200_u64) {
+            let transition = |state: &usize| table[*state];
+
+            let mut states = vec![start];
+            let (prefix, repeat) = loop {
+                let next = transition(states.last().expect("a state"));
+                if let Some(prefix) = states.iter().position(|&state| state == next) {
+                    break (prefix, states.len());
+                }
+                states.push(next);
+            };
+            let expected = Cycle {
+                prefix: prefix as u64,
+                period: (repeat - prefix) as u64,
+            };
+
+            prop_assert_eq!(find_cycle_hashed(start, transition), expected);
+            prop_assert_eq!(find_cycle_brent(start, transition), expected);
+
+            let stepped = (0..steps).fold(start, |state, _| transition(&state));
+            prop_assert_eq!(state_after(start, transition, steps), stepped);
+        }
+    }
+}
@@ -1,214 +1,381 @@
-pub fn solution(data: &str) -> Result<(u32, u32), Box<dyn std::error::Error>> {
-    let sum_a = puzzle_a(data)?;
-    let sum_b = puzzle_b(data)?;
+use std::collections::HashMap;
 
-    Ok((sum_a, sum_b))
+use crate::progress::Progress;
+
+/// Part B reads the spelled-out digits of the given vocabulary.
+pub fn solution(
+    data: &str,
+    vocabulary: &Vocabulary,
+    mode: Mode,
+    _progress: &mut dyn Progress,
+) -> Result<(i128, i128), Box<dyn std::error::Error>> {
+    let sum_a = puzzle_a(data, mode)?;
+    let sum_b = puzzle_b(data, vocabulary, mode)?;
+
+    Ok((sum_a.into(), sum_b.into()))
+}
+
+/// What to do with a line in which no digit can be found.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Mode {
+    /// Fail with a `CalibrationError::MissingDigit`.
+    Strict,
+    /// Leave the line out of the sum.
+    Lenient,
 }
 
-fn puzzle_a(data: &str) -> Result<u32, Box<dyn std::error::Error>> {
-    let sum: u32 = data
-        .split_whitespace()
-        .map(|x| {
-            (x.chars()
-                .filter(|c| c.is_numeric())
-                .nth(0)
-                .expect("first numeric character is missing")
-                .to_digit(10)
-                .expect("cannot convert first character to digit"))
-                * 10
-                + x.chars()
-                    .filter(|c| c.is_numeric())
-                    .nth_back(0)
-                    .expect("last numeric character is missing")
-                    .to_digit(10)
-                    .expect("cannot convert last character to digit")
-        })
-        .sum();
+#[derive(PartialEq, Debug)]
+pub struct MissingDigitError {
+    pub line_number: usize,
+    pub line: String,
+}
 
-    Ok(sum)
+impl std::fmt::Display for MissingDigitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {} has no digit: {:?}", self.line_number, self.line)
+    }
+}
+
+impl std::error::Error for MissingDigitError {}
+
+#[derive(PartialEq, Debug)]
+pub enum CalibrationError {
+    MissingDigit(MissingDigitError),
+    Overflow,
+}
+
+impl std::fmt::Display for CalibrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CalibrationError::MissingDigit(error) => error.fmt(f),
+            CalibrationError::Overflow => write!(f, "overflow: calibration does not fit in u64"),
+        }
+    }
+}
+
+impl std::error::Error for CalibrationError {}
+
+impl From<MissingDigitError> for CalibrationError {
+    fn from(error: MissingDigitError) -> Self {
+        CalibrationError::MissingDigit(error)
+    }
 }
 
-fn puzzle_b(data: &str) -> Result<u32, Box<dyn std::error::Error>> {
-    let sum: u32 = data
-        .split_whitespace()
-        .map(|x| string_to_numbers(x))
-        .map(|x| x.iter().next().unwrap() * 10 + x.iter().next_back().unwrap())
-        .sum();
+pub fn puzzle_a(data: &str, mode: Mode) -> Result<u64, CalibrationError> {
+    calibrate(data, &Vocabulary::digits(), mode)
+}
+
+pub fn puzzle_b(data: &str, vocabulary: &Vocabulary, mode: Mode) -> Result<u64, CalibrationError> {
+    calibrate(data, vocabulary, mode)
+}
+
+fn calibrate(data: &str, vocabulary: &Vocabulary, mode: Mode) -> Result<u64, CalibrationError> {
+    let mut sum: u64 = 0;
+
+    for (index, line) in data.lines().enumerate() {
+        for x in line.split_whitespace() {
+            // The last digit exists whenever the first one does.
+            match (vocabulary.first_digit(x), vocabulary.last_digit(x)) {
+                (Some(first), Some(last)) => {
+                    sum = sum
+                        .checked_add((first * 10 + last).into())
+                        .ok_or(CalibrationError::Overflow)?;
+                }
+                _ if mode == Mode::Lenient => {}
+                _ => {
+                    return Err(MissingDigitError {
+                        line_number: index + 1,
+                        line: line.to_string(),
+                    }
+                    .into())
+                }
+            }
+        }
+    }
 
     Ok(sum)
 }
 
-fn string_to_numbers(s: &str) -> Vec<u32> {
-    let length = s.len();
-    let mut list = vec![];
-    let mut cursor = 0;
-
-    loop {
-        let number = match true {
-            _ if s[cursor..].starts_with("0") => 0,
-            _ if s[cursor..].starts_with("1") => 1,
-            _ if s[cursor..].starts_with("2") => 2,
-            _ if s[cursor..].starts_with("3") => 3,
-            _ if s[cursor..].starts_with("4") => 4,
-            _ if s[cursor..].starts_with("5") => 5,
-            _ if s[cursor..].starts_with("6") => 6,
-            _ if s[cursor..].starts_with("7") => 7,
-            _ if s[cursor..].starts_with("8") => 8,
-            _ if s[cursor..].starts_with("9") => 9,
-            _ if s[cursor..].starts_with("one") => 1,
-            _ if s[cursor..].starts_with("two") => 2,
-            _ if s[cursor..].starts_with("three") => 3,
-            _ if s[cursor..].starts_with("four") => 4,
-            _ if s[cursor..].starts_with("five") => 5,
-            _ if s[cursor..].starts_with("six") => 6,
-            _ if s[cursor..].starts_with("seven") => 7,
-            _ if s[cursor..].starts_with("eight") => 8,
-            _ if s[cursor..].starts_with("nine") => 9,
-            _ => {
-                cursor = cursor + 1;
-                if cursor >= length {
-                    return list;
+/// The tokens that stand for digits: the digits themselves and the words that spell them out,
+/// stored in a trie to match all of them at once. Tokens are matched at every position, so
+/// overlapping words such as `eightwo` give both of their digits.
+pub struct Vocabulary {
+    nodes: Vec<TrieNode>,
+}
+
+#[derive(Default)]
+struct TrieNode {
+    children: HashMap<u8, usize>,
+    digit: Option<u32>,
+}
+
+impl Vocabulary {
+    /// The digits themselves and the given words. Fails for a word that stands for more than a
+    /// single digit.
+    pub fn new<'a>(words: impl IntoIterator<Item = (&'a str, u32)>) -> Result<Vocabulary, String> {
+        let mut vocabulary = Vocabulary {
+            nodes: vec![TrieNode::default()],
+        };
+
+        let digits = ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+        for (digit, token) in digits.into_iter().enumerate() {
+            vocabulary.insert(token, digit as u32);
+        }
+        for (word, digit) in words {
+            if digit > 9 {
+                return Err(format!("word {:?} stands for {}, not a digit", word, digit));
+            }
+            vocabulary.insert(word, digit);
+        }
+
+        Ok(vocabulary)
+    }
+
+    pub fn digits() -> Vocabulary {
+        Vocabulary::new([]).expect("no words to check")
+    }
+
+    pub fn english() -> Vocabulary {
+        Vocabulary::new([
+            ("one", 1),
+            ("two", 2),
+            ("three", 3),
+            ("four", 4),
+            ("five", 5),
+            ("six", 6),
+            ("seven", 7),
+            ("eight", 8),
+            ("nine", 9),
+        ])
+        .expect("words are digits")
+    }
+
+    pub fn dutch() -> Vocabulary {
+        Vocabulary::new([
+            ("een", 1),
+            ("twee", 2),
+            ("drie", 3),
+            ("vier", 4),
+            ("vijf", 5),
+            ("zes", 6),
+            ("zeven", 7),
+            ("acht", 8),
+            ("negen", 9),
+        ])
+        .expect("words are digits")
+    }
+
+    pub fn german() -> Vocabulary {
+        Vocabulary::new([
+            ("eins", 1),
+            ("zwei", 2),
+            ("drei", 3),
+            ("vier", 4),
+            ("fünf", 5),
+            ("sechs", 6),
+            ("sieben", 7),
+            ("acht", 8),
+            ("neun", 9),
+        ])
+        .expect("words are digits")
+    }
+
+    fn insert(&mut self, token: &str, digit: u32) {
+        let mut cursor = 0;
+        for byte in token.bytes() {
+            cursor = match self.nodes[cursor].children.get(&byte) {
+                Some(&child) => child,
+                None => {
+                    self.nodes.push(TrieNode::default());
+                    let child = self.nodes.len() - 1;
+                    let _ = self.nodes[cursor].children.insert(byte, child);
+                    child
                 }
+            };
+        }
+
+        self.nodes[cursor].digit = Some(digit);
+    }
 
-                continue;
+    /// The digit of the token that starts at the given byte offset, if any. Tokens only start with
+    /// the first byte of a character, so offsets within a multi-byte character never match.
+    fn digit_at(&self, line: &[u8], offset: usize) -> Option<u32> {
+        let mut cursor = 0;
+        for byte in line[offset..].iter() {
+            cursor = *self.nodes[cursor].children.get(byte)?;
+            if let Some(digit) = self.nodes[cursor].digit {
+                return Some(digit);
             }
-        };
+        }
+
+        None
+    }
+
+    pub fn first_digit(&self, line: &str) -> Option<u32> {
+        let bytes = line.as_bytes();
+        (0..bytes.len()).find_map(|offset| self.digit_at(bytes, offset))
+    }
+
+    pub fn last_digit(&self, line: &str) -> Option<u32> {
+        let bytes = line.as_bytes();
+        (0..bytes.len())
+            .rev()
+            .find_map(|offset| self.digit_at(bytes, offset))
+    }
+}
+
+impl std::str::FromStr for Vocabulary {
+    type Err = String;
 
-        cursor = cursor + 1;
-        list.push(number);
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "digits" => Ok(Vocabulary::digits()),
+            "english" => Ok(Vocabulary::english()),
+            "dutch" => Ok(Vocabulary::dutch()),
+            "german" => Ok(Vocabulary::german()),
+            _ => Err(format!("unknown vocabulary {:?}", name)),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use super::*;
 
-    struct TestCase {
-        input: String,
-        expected_output_a: u32,
-        expected_output_b: u32,
+    #[test]
+    fn vocabularies() {
+        let test_cases = vec![
+            (Vocabulary::dutch(), "zevenachtwee", 72),
+            (Vocabulary::dutch(), "xnegeneenx", 91),
+            (Vocabulary::german(), "fünfzweiunddreißig", 53),
+            (Vocabulary::german(), "äeinsö", 11),
+            (Vocabulary::english(), "vierfünf4", 44),
+            (
+                Vocabulary::new([("ichi", 1), ("ni", 2)]).expect("words are digits"),
+                "ichini",
+                12,
+            ),
+        ];
+
+        for (vocabulary, input, expected_output) in test_cases {
+            let output = puzzle_b(input, &vocabulary, Mode::Strict).expect("b calculation failed");
+            assert_eq!(output, expected_output, "input: {:?}", input);
+        }
+
+        assert!(Vocabulary::new([("ten", 10)]).is_err());
     }
 
     #[test]
-    fn puzzle() {
-        let test_cases = vec![
-            TestCase {
-                input: "".into(),
-                expected_output_a: 0,
-                expected_output_b: 0,
-            },
-            TestCase {
-                input: "00".into(),
-                expected_output_a: 0,
-                expected_output_b: 0,
-            },
-            TestCase {
-                input: "1".into(),
-                expected_output_a: 11,
-                expected_output_b: 11,
-            },
-            TestCase {
-                input: "12".into(),
-                expected_output_a: 12,
-                expected_output_b: 12,
-            },
-            TestCase {
-                input: "102".into(),
-                expected_output_a: 12,
-                expected_output_b: 12,
-            },
-            TestCase {
-                input: "5a2".into(),
-                expected_output_a: 52,
-                expected_output_b: 52,
-            },
-            TestCase {
-                input: "a9b54c2ag".into(),
-                expected_output_a: 92,
-                expected_output_b: 92,
-            },
-            TestCase {
-                input: "12
-34"
-                .into(),
-                expected_output_a: 46,
-                expected_output_b: 46,
-            },
-            TestCase {
-                input: "a12b
-z3e4fg"
-                    .into(),
-                expected_output_a: 46,
-                expected_output_b: 46,
-            },
-            TestCase {
-                input: "a12b
-z3e4fg
-5a2b3g1u"
-                    .into(),
-                expected_output_a: 97,
-                expected_output_b: 97,
-            },
-            TestCase {
-                input: "1abc2
+    fn lines_without_digits() {
+        let input = "1abc2
 pqr3stu8vwx
-a1b2c3d4e5f
-treb7uchet"
-                    .into(),
-                expected_output_a: 142,
-                expected_output_b: 142,
-            },
-            TestCase {
-                input: "1two".into(),
-                expected_output_a: 11,
-                expected_output_b: 12,
-            },
-            TestCase {
-                input: "three5two".into(),
-                expected_output_a: 55,
-                expected_output_b: 32,
-            },
-            TestCase {
-                input: "xthree5twoc".into(),
-                expected_output_a: 55,
-                expected_output_b: 32,
-            },
-            TestCase {
-                input: "xthrebethree5twoc".into(),
-                expected_output_a: 55,
-                expected_output_b: 32,
-            },
-            TestCase {
-                input: "two1nine
-eightwo8three
-abcone2threexyz
-xtwone3four
-4nineeightseven2
-zoneight234
-7pqrstsixteen"
-                    .into(),
-                expected_output_a: 297,
-                expected_output_b: 281,
-            },
-            TestCase {
-                input: "1eightwo".into(),
-                expected_output_a: 11,
-                expected_output_b: 12,
-            },
-        ];
 
-        for test_case in test_cases {
-            let output = puzzle_a(&test_case.input).expect("a calculation failed");
-            assert_eq!(
-                output, test_case.expected_output_a,
-                "input: {:?}",
-                test_case.input
+abcdef
+treb7uchet";
+
+        let error = MissingDigitError {
+            line_number: 4,
+            line: "abcdef".into(),
+        };
+        assert_eq!(puzzle_a(input, Mode::Strict), Err(error.into()));
+
+        let output = puzzle_a(input, Mode::Lenient).expect("a calculation failed");
+        assert_eq!(output, 12 + 38 + 77);
+
+        let input = "two1nine
+xyz";
+
+        let error = MissingDigitError {
+            line_number: 2,
+            line: "xyz".into(),
+        };
+        assert_eq!(
+            puzzle_b(input, &Vocabulary::english(), Mode::Strict),
+            Err(error.into())
+        );
+
+        let output =
+            puzzle_b(input, &Vocabulary::english(), Mode::Lenient).expect("b calculation failed");
+        assert_eq!(output, 29);
+    }
+
+    const ENGLISH: [(&str, u32); 9] = [
+        ("one", 1),
+        ("two", 2),
+        ("three", 3),
+        ("four", 4),
+        ("five", 5),
+        ("six", 6),
+        ("seven", 7),
+        ("eight", 8),
+        ("nine", 9),
+    ];
+
+    /// Tries every token at every offset of every word, without a trie.
+    fn naive_calibration(data: &str, words: &[(&str, u32)], mode: Mode) -> Result<u64, usize> {
+        let digits: Vec<(String, u32)> = (0..10).map(|digit| (digit.to_string(), digit)).collect();
+        let tokens: Vec<(&str, u32)> = digits
+            .iter()
+            .map(|(token, digit)| (token.as_str(), *digit))
+            .chain(words.iter().copied())
+            .collect();
+
+        let digit_at = |word: &str, offset: usize| {
+            let rest = word.get(offset..)?;
+            tokens
+                .iter()
+                .find(|(token, _)| rest.starts_with(token))
+                .map(|&(_, digit)| digit)
+        };
+
+        let mut sum = 0;
+        for (index, line) in data.lines().enumerate() {
+            for word in line.split_whitespace() {
+                let first = (0..word.len()).find_map(|offset| digit_at(word, offset));
+                let last = (0..word.len())
+                    .rev()
+                    .find_map(|offset| digit_at(word, offset));
+                match (first, last) {
+                    (Some(first), Some(last)) => sum += u64::from(first * 10 + last),
+                    _ if mode == Mode::Lenient => {}
+                    _ => return Err(index + 1),
+                }
+            }
+        }
+
+        Ok(sum)
+    }
+
+    /// Lines glued together from digits, digit words, parts of digit words and other text, so
+    /// that words overlap and almost match.
+    fn calibration_document() -> impl Strategy<Value = String> {
+        let fragment = prop::sample::select(vec![
+            "1", "2", "5", "9", "0", "one", "two", "three", "eight", "nine", "seven", "on", "tw",
+            "thre", "eigh", "ight", "ne", "e", "n", "x", "ü", " ",
+        ]);
+        let line = prop::collection::vec(fragment, 0..12).prop_map(|fragments| fragments.concat());
+
+        prop::collection::vec(line, 0..8).prop_map(|lines| lines.join("\n"))
+    }
+
+    proptest! {
+        #[test]
+        fn calibration_matches_naive_scanner(data in calibration_document(), lenient in any::<bool>()) {
+            let mode = if lenient { Mode::Lenient } else { Mode::Strict };
+            let line_number = |error: CalibrationError| match error {
+                CalibrationError::MissingDigit(error) => error.line_number,
+                CalibrationError::Overflow => 0,
+            };
+
+            prop_assert_eq!(
+                puzzle_a(&data, mode).map_err(line_number),
+                naive_calibration(&data, &[], mode)
             );
-            let output = puzzle_b(&test_case.input).expect("b calculation failed");
-            assert_eq!(
-                output, test_case.expected_output_b,
-                "input: {:?}",
-                test_case.input
+            prop_assert_eq!(
+                puzzle_b(&data, &Vocabulary::english(), mode).map_err(line_number),
+                naive_calibration(&data, &ENGLISH, mode)
             );
         }
     }
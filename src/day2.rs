@@ -1,133 +1,209 @@
-pub fn solution(data: &str) -> Result<(u32, u32), Box<dyn std::error::Error>> {
-    let sum_a = puzzle_a(&data)?;
-    let sum_b = puzzle_b(&data)?;
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
 
-    Ok((sum_a, sum_b))
+use crate::progress::Progress;
+
+/// Part A counts the games that are possible with the given bag.
+pub fn solution(
+    data: &str,
+    bag: &CubeSet,
+    _progress: &mut dyn Progress,
+) -> Result<(i128, i128), Box<dyn std::error::Error>> {
+    let sum_a = puzzle_a(data, bag)?;
+    let sum_b = puzzle_b(data)?;
+
+    Ok((sum_a.into(), sum_b.into()))
 }
 
-enum Colour {
-    Red,
-    Green,
-    Blue,
+/// An amount of cubes per colour. Colours that are not in the set have no cubes.
+#[derive(Default, Clone, PartialEq, Debug)]
+pub struct CubeSet {
+    cubes: BTreeMap<String, u32>,
 }
 
-struct Cube {
-    colour: Colour,
-    amount: u32,
+impl CubeSet {
+    /// The bag the elf asks about in the puzzle.
+    pub fn puzzle_bag() -> CubeSet {
+        "12 red, 13 green, 14 blue"
+            .parse()
+            .expect("puzzle bag is a valid cube set")
+    }
+
+    pub fn amount(&self, colour: &str) -> u32 {
+        self.cubes.get(colour).copied().unwrap_or(0)
+    }
+
+    /// Whether every cube of this set can be drawn from the bag.
+    pub fn fits_in(&self, bag: &CubeSet) -> bool {
+        self.cubes
+            .iter()
+            .all(|(colour, &amount)| amount <= bag.amount(colour))
+    }
+
+    /// The product of the amounts of all colours in the set, or `None` if it does not fit in a u64.
+    pub fn power(&self) -> Option<u64> {
+        self.cubes
+            .values()
+            .try_fold(1_u64, |power, &amount| power.checked_mul(amount.into()))
+    }
 }
 
-struct CubeSet {
-    red: u32,
-    green: u32,
-    blue: u32,
+impl FromStr for CubeSet {
+    type Err = String;
+
+    /// Parses cubes such as `3 blue, 4 red`. Cubes of the same colour are added up.
+    fn from_str(cube_set: &str) -> Result<Self, Self::Err> {
+        let mut cubes: BTreeMap<String, u32> = BTreeMap::new();
+
+        for cube in cube_set.split_terminator(',') {
+            let mut cube_iter = cube.split_whitespace();
+            let (Some(amount), Some(colour), None) =
+                (cube_iter.next(), cube_iter.next(), cube_iter.next())
+            else {
+                return Err(format!(
+                    "parse error: cube {:?} is not an amount and colour",
+                    cube
+                ));
+            };
+
+            let amount: u32 = amount
+                .parse()
+                .map_err(|_| format!("parse error: cube amount {:?} is not a number", amount))?;
+
+            let total = cubes.entry(colour.to_string()).or_insert(0);
+            *total = total
+                .checked_add(amount)
+                .ok_or(format!("overflow: too many {} cubes", colour))?;
+        }
+
+        Ok(CubeSet { cubes })
+    }
 }
 
-struct GameRecord {
-    id: u32,
-    cube_sets: Vec<CubeSet>,
+pub struct GameRecord {
+    pub id: u32,
+    pub cube_sets: Vec<CubeSet>,
 }
 
-fn extract_game_records<'a>(data: &'a str) -> impl Iterator<Item = GameRecord> + 'a {
-    data.lines().map(|line| {
-        let line_prefix = "Game ";
-        assert!(line.starts_with(line_prefix));
+impl GameRecord {
+    pub fn is_possible_with(&self, bag: &CubeSet) -> bool {
+        self.cube_sets.iter().all(|cube_set| cube_set.fits_in(bag))
+    }
 
-        let mut colon_split = line[line_prefix.len()..].split_terminator(':');
+    /// The fewest cubes of each of the given colours the bag must have held for this game to be
+    /// possible. A colour the game never draws gets no cubes, which makes the power zero.
+    pub fn minimum_bag(&self, colours: &BTreeSet<&str>) -> CubeSet {
+        let mut cubes: BTreeMap<String, u32> = colours
+            .iter()
+            .map(|colour| (colour.to_string(), 0))
+            .collect();
 
-        let id: u32 = colon_split
-            .next()
-            .expect("no colon found")
-            .parse()
-            .expect("string slice cannot be parsed as integer");
+        for cube_set in self.cube_sets.iter() {
+            for (colour, &amount) in cube_set.cubes.iter() {
+                let minimum = cubes.entry(colour.clone()).or_insert(0);
+                *minimum = (*minimum).max(amount);
+            }
+        }
 
-        let cube_sets_str = colon_split
-            .next()
-            .expect("no game record after colon")
-            .trim();
+        CubeSet { cubes }
+    }
+}
+
+impl FromStr for GameRecord {
+    type Err = String;
+
+    /// Parses a line such as `Game 1: 3 blue, 4 red; 1 red, 2 green`.
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let (game, cube_sets_str) = line
+            .split_once(':')
+            .ok_or(format!("parse error: no colon in game record {:?}", line))?;
+
+        let id: u32 = game
+            .strip_prefix("Game ")
+            .and_then(|id| id.trim().parse().ok())
+            .ok_or(format!("parse error: no game id in {:?}", game))?;
 
         let cube_sets = cube_sets_str
             .split_terminator(';')
-            .map(|cube_set| {
-                let cubes = cube_set.split_terminator(',').map(|cube| {
-                    let mut cubes = cube.split_whitespace();
-                    let amount: u32 = cubes
-                        .next()
-                        .expect("no number found in cube")
-                        .parse()
-                        .expect("number could not be parsed");
-
-                    let colour: Colour = match cubes.next().expect("no number found in cube") {
-                        "red" => Colour::Red,
-                        "green" => Colour::Green,
-                        "blue" => Colour::Blue,
-                        _ => panic!("cannot parse cube colour"),
-                    };
-
-                    Cube { amount, colour }
-                });
-
-                let mut red = 0;
-                let mut green = 0;
-                let mut blue = 0;
-
-                for cube in cubes {
-                    match cube.colour {
-                        Colour::Red => red = red + cube.amount,
-                        Colour::Green => green = green + cube.amount,
-                        Colour::Blue => blue = blue + cube.amount,
-                    }
-                }
-
-                CubeSet { red, green, blue }
-            })
-            .collect();
+            .map(CubeSet::from_str)
+            .collect::<Result<Vec<CubeSet>, String>>()?;
 
-        GameRecord { id, cube_sets }
-    })
+        Ok(GameRecord { id, cube_sets })
+    }
 }
 
-fn puzzle_a(data: &str) -> Result<u32, Box<dyn std::error::Error>> {
-    let game_records = extract_game_records(data);
-
-    let sum = game_records
-        .filter(|game_record| {
-            // The record is included if there is no cube set with excessive cubes of a particular
-            // colour.
-            !game_record
-                .cube_sets
-                .iter()
-                .any(|cube_set| cube_set.red > 12 || cube_set.green > 13 || cube_set.blue > 14)
-        })
-        .map(|game_record| game_record.id)
+/// All game records, with queries across the games.
+pub struct CubeGame {
+    pub records: Vec<GameRecord>,
+}
+
+impl CubeGame {
+    /// Every colour drawn in any of the games.
+    pub fn colours(&self) -> BTreeSet<&str> {
+        self.records
+            .iter()
+            .flat_map(|record| record.cube_sets.iter())
+            .flat_map(|cube_set| cube_set.cubes.keys())
+            .map(|colour| colour.as_str())
+            .collect()
+    }
+
+    pub fn possible_games<'a>(
+        &'a self,
+        bag: &'a CubeSet,
+    ) -> impl Iterator<Item = &'a GameRecord> + 'a {
+        self.records
+            .iter()
+            .filter(move |record| record.is_possible_with(bag))
+    }
+
+    /// The minimum bag of every game, by game id. A bag lists every colour of the whole input,
+    /// so that a colour a game never draws makes its power zero.
+    pub fn minimum_bags(&self) -> Vec<(u32, CubeSet)> {
+        let colours = self.colours();
+
+        self.records
+            .iter()
+            .map(|record| (record.id, record.minimum_bag(&colours)))
+            .collect()
+    }
+}
+
+impl FromStr for CubeGame {
+    type Err = String;
+
+    fn from_str(data: &str) -> Result<Self, Self::Err> {
+        let records = data
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(GameRecord::from_str)
+            .collect::<Result<Vec<GameRecord>, String>>()?;
+
+        Ok(CubeGame { records })
+    }
+}
+
+pub fn puzzle_a(data: &str, bag: &CubeSet) -> Result<u64, Box<dyn std::error::Error>> {
+    let cube_game: CubeGame = data.parse()?;
+
+    let sum = cube_game
+        .possible_games(bag)
+        .map(|record| u64::from(record.id))
         .sum();
 
     Ok(sum)
 }
 
-fn puzzle_b(data: &str) -> Result<u32, Box<dyn std::error::Error>> {
-    let game_records = extract_game_records(data);
-    let sum = game_records
-        .map(|_game_record| {
-            let mut red = 0;
-            let mut green = 0;
-            let mut blue = 0;
-
-            for cube_set in _game_record.cube_sets.iter() {
-                if cube_set.red > red {
-                    red = cube_set.red
-                }
-                if cube_set.green > green {
-                    green = cube_set.green
-                }
-                if cube_set.blue > blue {
-                    blue = cube_set.blue
-                }
-            }
+pub fn puzzle_b(data: &str) -> Result<u64, Box<dyn std::error::Error>> {
+    let cube_game: CubeGame = data.parse()?;
 
-            CubeSet { red, green, blue }
+    let sum = cube_game
+        .minimum_bags()
+        .iter()
+        .try_fold(0_u64, |sum, (_, bag)| {
+            bag.power().and_then(|power| sum.checked_add(power))
         })
-        .map(|cube_set| cube_set.red * cube_set.green * cube_set.blue)
-        .sum();
+        .ok_or("overflow: sum of powers does not fit in u64")?;
 
     Ok(sum)
 }
@@ -135,49 +211,64 @@ fn puzzle_b(data: &str) -> Result<u32, Box<dyn std::error::Error>> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    struct TestCase {
-        input: &'static str,
-        expected_output_a: u32,
-        expected_output_b: u32,
+
+    #[test]
+    fn queries() {
+        let cube_game: CubeGame = "Game 1: 2 yellow, 1 red; 3 yellow
+Game 2: 4 yellow; 1 purple, 2 red
+Game 7: 1 red, 1 red"
+            .parse()
+            .expect("parsing games");
+
+        let bag: CubeSet = "3 yellow, 2 red".parse().expect("parsing bag");
+        let possible_ids: Vec<u32> = cube_game
+            .possible_games(&bag)
+            .map(|record| record.id)
+            .collect();
+        assert_eq!(possible_ids, vec![1, 7]);
+
+        assert_eq!(
+            cube_game.colours().into_iter().collect::<Vec<&str>>(),
+            vec!["purple", "red", "yellow"]
+        );
+
+        let minimum_bags = cube_game.minimum_bags();
+        assert_eq!(minimum_bags[1].0, 2);
+        assert_eq!(minimum_bags[1].1.amount("purple"), 1);
+        assert_eq!(minimum_bags[1].1.amount("yellow"), 4);
+        assert_eq!(minimum_bags[1].1.power(), Some(8));
+        // Game 1 never draws purple.
+        assert_eq!(minimum_bags[0].1.power(), Some(0));
+        assert_eq!(minimum_bags[2].1.amount("red"), 2);
+
+        // A record alone agrees with the game, given the colours of the whole game.
+        let colours = cube_game.colours();
+        assert_eq!(
+            cube_game.records[0].minimum_bag(&colours),
+            minimum_bags[0].1
+        );
+        let own_colours = BTreeSet::from(["red", "yellow"]);
+        assert_eq!(
+            cube_game.records[0].minimum_bag(&own_colours).power(),
+            Some(3)
+        );
+
+        assert!("Game x: 1 red".parse::<CubeGame>().is_err());
+        assert!("Game 1: red".parse::<CubeGame>().is_err());
+        assert!("Game 1 1 red".parse::<CubeGame>().is_err());
+        assert!("Game 1: 4294967295 red, 1 red".parse::<CubeGame>().is_err());
     }
 
     #[test]
-    fn puzzle() {
-        let test_cases = vec![
-            TestCase {
-                input: "",
-                expected_output_a: 0,
-                expected_output_b: 0,
-            },
-            TestCase {
-                input: "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green",
-                expected_output_a: 1,
-                expected_output_b: 4 * 2 * 6,
-            },
-            TestCase {
-                input: "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
-Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
-Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
-Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
-Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green",
-                expected_output_a: 8,
-                expected_output_b: 2286,
-            },
-        ];
-
-        for test_case in test_cases {
-            let output = puzzle_a(test_case.input).expect("calculation a failed");
-            assert_eq!(
-                output, test_case.expected_output_a,
-                "input: {:?}",
-                test_case.input
-            );
-            let output = puzzle_b(test_case.input).expect("calculation b failed");
-            assert_eq!(
-                output, test_case.expected_output_b,
-                "input: {:?}",
-                test_case.input
-            );
-        }
+    fn large_powers() {
+        let output = puzzle_b("Game 1: 100000 red, 100000 blue").expect("solving puzzle b");
+        assert_eq!(output, 10_000_000_000);
+
+        let game = "Game 1: 4294967295 red, 4294967295 blue, 4294967295 green";
+        assert!(puzzle_b(game).is_err());
+
+        let games = "Game 1: 4294967295 red, 4294967295 blue
+Game 2: 4294967295 red, 4294967295 blue";
+        assert!(puzzle_b(games).is_err());
     }
 }
@@ -1,50 +1,132 @@
-pub fn solution(data: &str) -> Result<(u32, u32), Box<dyn std::error::Error>> {
-    let sum_a = puzzle_a(&data)?;
-    let sum_b = puzzle_b(&data)?;
+use crate::graph::Graph;
+use crate::progress::Progress;
 
-    Ok((sum_a, sum_b))
+pub fn solution(
+    data: &str,
+    _progress: &mut dyn Progress,
+) -> Result<(i128, i128), Box<dyn std::error::Error>> {
+    let sum_a = puzzle_a(data)?;
+    let sum_b = puzzle_b(data)?;
+
+    Ok((sum_a.into(), sum_b.into()))
 }
 
-fn puzzle_a(_data: &str) -> Result<u32, Box<dyn std::error::Error>> {
-    Ok(0)
+pub fn puzzle_a(data: &str) -> Result<u64, Box<dyn std::error::Error>> {
+    let cut = find_cut(data)?;
+    if cut.wires.len() != 3 {
+        return Err(format!("expected to cut 3 wires, found {}", cut.wires.len()).into());
+    }
+
+    let (size_a, size_b) = cut.component_sizes;
+    let product = u64::try_from(size_a)?
+        .checked_mul(u64::try_from(size_b)?)
+        .ok_or("overflow: product of group sizes does not fit in u64")?;
+
+    Ok(product)
 }
 
-fn puzzle_b(_data: &str) -> Result<u32, Box<dyn std::error::Error>> {
+// Day 25 has no second puzzle.
+pub fn puzzle_b(_data: &str) -> Result<u64, Box<dyn std::error::Error>> {
     Ok(0)
 }
 
+/// The wires whose removal splits the components into two groups, and the sizes of these groups.
+pub struct Cut {
+    pub wires: Vec<(String, String)>,
+    pub component_sizes: (usize, usize),
+}
+
+pub fn find_cut(data: &str) -> Result<Cut, Box<dyn std::error::Error>> {
+    let diagram = extract_wiring_diagram(data)?;
+    let cut = diagram
+        .minimum_cut()
+        .ok_or("wiring diagram needs at least two components")?;
+
+    // Every wire is an edge in both directions, of which one leaves the first side.
+    let wires = diagram
+        .edges()
+        .iter()
+        .filter(|wire| cut.is_first_side[wire.from] && !cut.is_first_side[wire.to])
+        .map(|wire| {
+            (
+                diagram.name(wire.from).to_string(),
+                diagram.name(wire.to).to_string(),
+            )
+        })
+        .collect();
+
+    let size_first_side = cut
+        .is_first_side
+        .iter()
+        .filter(|&&is_first| is_first)
+        .count();
+
+    Ok(Cut {
+        wires,
+        component_sizes: (size_first_side, diagram.len() - size_first_side),
+    })
+}
+
+/// The components connected by wires that each can carry a flow of one.
+pub type WiringDiagram<'a> = Graph<&'a str, u64>;
+
+/// Parses lines such as `jqt: rhn xhk`, which wire a component to each of the components after
+/// the colon.
+pub fn extract_wiring_diagram(data: &str) -> Result<WiringDiagram<'_>, Box<dyn std::error::Error>> {
+    let mut diagram = WiringDiagram::new();
+
+    for line in data.lines().filter(|line| !line.trim().is_empty()) {
+        let (component, connected) = line
+            .split_once(':')
+            .ok_or_else(|| format!("parse error: no colon in line {:?}", line))?;
+
+        let component = diagram.intern(component.trim());
+
+        for other in connected.split_whitespace() {
+            let other = diagram.intern(other);
+            diagram.add_undirected_edge(component, other, 1);
+        }
+    }
+
+    Ok(diagram)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    struct TestCase {
-        input: String,
-        expected_output_a: u32,
-        expected_output_b: u32,
-    }
+    const EXAMPLE: &str = "jqt: rhn xhk nvd
+rsh: frs pzl lsr
+xhk: hfx
+cmg: qnr nvd lhk bvb
+rhn: xhk bvb hfx
+bvb: xhk hfx
+pzl: lsr hfx nvd
+qnr: nvd
+ntq: jqt hfx bvb xhk
+nvd: lhk
+lsr: lhk
+rzs: qnr cmg lsr rsh
+frs: qnr lhk lsr";
 
     #[test]
-    fn puzzle() {
-        let test_cases = vec![TestCase {
-            input: "".into(),
-            expected_output_a: 0,
-            expected_output_b: 0,
-        }];
-
-        for test_case in test_cases {
-            let output = puzzle_a(&test_case.input).expect("solving puzzle a");
-            assert_eq!(
-                output, test_case.expected_output_a,
-                "input: {}",
-                test_case.input,
-            );
-
-            let output = puzzle_b(&test_case.input).expect("solving puzzle b");
-            assert_eq!(
-                output, test_case.expected_output_b,
-                "input: {}",
-                test_case.input,
-            );
-        }
+    fn cut_wires() {
+        let cut = find_cut(EXAMPLE).expect("finding cut");
+
+        let mut wires: Vec<(String, String)> = cut
+            .wires
+            .into_iter()
+            .map(|(a, b)| if a < b { (a, b) } else { (b, a) })
+            .collect();
+        wires.sort();
+
+        assert_eq!(
+            wires,
+            vec![
+                ("bvb".to_string(), "cmg".to_string()),
+                ("hfx".to_string(), "pzl".to_string()),
+                ("jqt".to_string(), "nvd".to_string()),
+            ]
+        );
     }
 }
@@ -1,263 +1,258 @@
-pub fn solution(data: &str) -> Result<(u32, u32), Box<dyn std::error::Error>> {
-    let sum_a = puzzle_a(&data)?;
-    let sum_b = puzzle_b(&data)?;
+use crate::grid::{Grid, Position};
+use crate::progress::Progress;
 
-    Ok((sum_a, sum_b))
+pub fn solution(
+    data: &str,
+    _progress: &mut dyn Progress,
+) -> Result<(i128, i128), Box<dyn std::error::Error>> {
+    let sum_a = puzzle_a(data)?;
+    let sum_b = puzzle_b(data)?;
+
+    Ok((sum_a.into(), sum_b.into()))
 }
 
-fn puzzle_a(data: &str) -> Result<u32, Box<dyn std::error::Error>> {
-    let width = data
-        .lines()
-        .next()
-        .expect("data does not contain a single line")
-        .len();
-
-    let empty_line = ".".repeat(width);
-
-    use std::iter::once;
-
-    // Prepend and append the empty line for easier processing.
-    let lines: Vec<&str> = once(empty_line.as_str())
-        .chain(data.split_terminator('\n'))
-        .chain(once(empty_line.as_str()))
-        .collect();
-
-    let sum: u32 = lines
-        .windows(3)
-        .map(|windows| {
-            let (upper_line, current_line, lower_line) = (windows[0], windows[1], windows[2]);
-
-            let numbers_found = find_numbers(current_line);
-
-            numbers_found
-                .iter()
-                .filter(|(x, y)| {
-                    let check_symbol_positions_start = if *y <= 0 { 0 } else { *y - 1 };
-                    let check_symbol_positions_end =
-                        std::cmp::min(width as u32 - 1, x.to_string().len() as u32 + *y);
-
-                    for index in check_symbol_positions_start..=check_symbol_positions_end {
-                        if let Some(character) = upper_line.chars().nth(index as usize) {
-                            if !character.is_numeric() && character != '.' {
-                                return true;
-                            }
-                        }
-                        if let Some(character) = current_line.chars().nth(index as usize) {
-                            if !character.is_numeric() && character != '.' {
-                                return true;
-                            }
-                        }
-                        if let Some(character) = lower_line.chars().nth(index as usize) {
-                            if !character.is_numeric() && character != '.' {
-                                return true;
-                            }
-                        }
-                    }
+pub fn puzzle_a(data: &str) -> Result<u64, Box<dyn std::error::Error>> {
+    let schematic = Schematic::parse(data)?;
 
-                    false
-                })
-                .map(|&(number, _)| number)
-                .collect::<Vec<u32>>()
-        })
-        .flat_map(|x| x)
-        .sum();
+    // Every number is below 2^32, and there are fewer than 2^32 of them in memory.
+    let sum = schematic
+        .part_numbers()
+        .map(|number| u64::from(number.value))
+        .sum::<u64>();
 
     Ok(sum)
 }
 
-fn puzzle_b(data: &str) -> Result<u32, Box<dyn std::error::Error>> {
-    let width = data
-        .lines()
-        .next()
-        .expect("data does not contain a single line")
-        .len();
-
-    let empty_line = ".".repeat(width);
-
-    use std::iter::once;
-
-    // Prepend and append the empty line for easier processing.
-    let lines: Vec<&str> = once(empty_line.as_str())
-        .chain(data.split_terminator('\n'))
-        .chain(once(empty_line.as_str()))
-        .collect();
-
-    let sum: u32 = lines
-        .windows(3)
-        .map(|windows| {
-            let (upper_line, current_line, lower_line) = (windows[0], windows[1], windows[2]);
-
-            let gears_found = find_gears(current_line);
-
-            let numbers_upper = find_numbers(upper_line);
-            let numbers_current = find_numbers(current_line);
-            let numbers_lower = find_numbers(lower_line);
-            let number_lines = vec![numbers_upper, numbers_current, numbers_lower];
-
-            gears_found
-                .iter()
-                .filter_map(|gear_position| {
-                    // Iterate over the numbers found. For each number, check if the gear is within
-                    // range. If so, the number is collected. If the collected amount of numbers is
-                    // 2, then return Some(first*second)
-                    let mut numbers_in_range: Vec<u32> = vec![];
-
-                    let _ = number_lines.iter().for_each(|number_line| {
-                        let _ = number_line.iter().for_each(|(number, position)| {
-                            // Determine the range the gear must be in.
-                            let check_symbol_positions_start =
-                                if *position <= 0 { 0 } else { *position - 1 };
-
-                            let check_symbol_positions_end = std::cmp::min(
-                                width as u32 - 1,
-                                number.to_string().len() as u32 + *position,
-                            );
-
-                            if *gear_position >= check_symbol_positions_start
-                                && *gear_position <= check_symbol_positions_end
-                            {
-                                numbers_in_range.push(*number);
-                            }
-                        });
-                    });
-
-                    match numbers_in_range.len() {
-                        2 => Some(numbers_in_range[0] * numbers_in_range[1]),
-                        _ => None,
-                    }
-                })
-                .collect::<Vec<u32>>()
+pub fn puzzle_b(data: &str) -> Result<u64, Box<dyn std::error::Error>> {
+    let schematic = Schematic::parse(data)?;
+
+    let sum = schematic
+        .ratios('*', 2)
+        .try_fold(0_u64, |sum, (_, gear_ratio)| {
+            gear_ratio.and_then(|gear_ratio| sum.checked_add(gear_ratio))
         })
-        .flat_map(|x| x)
-        .sum();
+        .ok_or("overflow: sum of gear ratios does not fit in u64")?;
 
     Ok(sum)
 }
 
-fn find_gears(line: &str) -> Vec<u32> {
-    line.chars()
-        .enumerate()
-        .filter(|(_, symbol)| *symbol == '*')
-        .map(|(position, _)| position as u32)
-        .collect()
+/// A number in the schematic, spanning the columns from `start` up to `end` of a row.
+pub struct NumberSpan {
+    pub value: u32,
+    pub row: usize,
+    pub start: usize,
+    pub end: usize,
 }
 
-fn find_numbers(_line: &str) -> Vec<(u32, u32)> {
-    let mut numbers = vec![];
-
-    let mut number_found = false;
-    let mut digits = vec![];
-    let mut position_first_digit = 0;
-
-    for (index, character) in _line.chars().enumerate() {
-        match number_found {
-            true => match character.is_numeric() {
-                true => digits.push(
-                    character
-                        .to_digit(10)
-                        .expect("char is numeric but cannot be converted to digit"),
-                ),
-                false => {
-                    let n = digits_to_number(digits);
-                    numbers.push((n, position_first_digit as u32));
-
-                    // Calculate a number found from the digits.
-                    digits = vec![];
-                    number_found = false;
-                }
-            },
-            false => match character.is_numeric() {
-                true => {
-                    number_found = true;
-                    position_first_digit = index;
-                    digits.push(
-                        character
-                            .to_digit(10)
-                            .expect("char is numeric but cannot be converted to digit"),
-                    );
+/// A character in the schematic that is neither a digit nor a `.`.
+pub struct Symbol {
+    pub character: char,
+    pub row: usize,
+    pub column: usize,
+}
+
+/// The engine schematic, parsed once into its numbers and symbols, with for every symbol the
+/// numbers that are adjacent to it, diagonals included.
+pub struct Schematic {
+    numbers: Vec<NumberSpan>,
+    symbols: Vec<Symbol>,
+    // Indices into `numbers` for every symbol, in the same order as `symbols`.
+    adjacent_numbers: Vec<Vec<usize>>,
+}
+
+impl Schematic {
+    /// Parses the schematic by characters rather than bytes, so symbols may be any character.
+    /// Line endings may be `\n`, `\r\n` or `\r`, but every row must be equally wide.
+    pub fn parse(data: &str) -> Result<Schematic, Box<dyn std::error::Error>> {
+        let grid = Grid::parse(data)?;
+
+        let mut numbers: Vec<NumberSpan> = vec![];
+        for (row, cells) in grid.rows().enumerate() {
+            let mut in_number = false;
+
+            for (column, character) in cells.iter().enumerate() {
+                match character.to_digit(10) {
+                    Some(digit) => {
+                        if !in_number {
+                            numbers.push(NumberSpan {
+                                value: 0,
+                                row,
+                                start: column,
+                                end: column,
+                            });
+                        }
+
+                        let number = numbers.last_mut().expect("number was just pushed");
+                        number.value = number
+                            .value
+                            .checked_mul(10)
+                            .and_then(|value| value.checked_add(digit))
+                            .ok_or_else(|| {
+                                format!("overflow: number in row {} does not fit in u32", row + 1)
+                            })?;
+                        number.end = column + 1;
+                        in_number = true;
+                    }
+                    None => in_number = false,
                 }
-                false => {}
-            },
+            }
         }
-    }
 
-    if number_found {
-        let n = digits_to_number(digits);
-        numbers.push((n, position_first_digit as u32));
-    }
+        let symbols: Vec<Symbol> = grid
+            .find_all(|character| !character.is_ascii_digit() && *character != '.')
+            .map(|position| Symbol {
+                character: grid[position],
+                row: position.row,
+                column: position.column,
+            })
+            .collect();
+
+        // For every cell, the index of the number that covers it.
+        let mut number_at = Grid::filled(grid.width(), grid.height(), None);
+        for (index, number) in numbers.iter().enumerate() {
+            for column in number.start..number.end {
+                number_at[Position::new(number.row, column)] = Some(index);
+            }
+        }
 
-    numbers
-}
+        let adjacent_numbers = symbols
+            .iter()
+            .map(|symbol| {
+                let mut adjacent: Vec<usize> = vec![];
+
+                for neighbour in number_at.neighbours8(Position::new(symbol.row, symbol.column)) {
+                    // A number spanning several adjacent cells is only counted once.
+                    if let Some(number) = number_at[neighbour] {
+                        if !adjacent.contains(&number) {
+                            adjacent.push(number);
+                        }
+                    }
+                }
 
-fn digits_to_number(digits: Vec<u32>) -> u32 {
-    let amount_of_digits = digits.len();
+                adjacent
+            })
+            .collect();
 
-    let mut n = 0;
+        Ok(Schematic {
+            numbers,
+            symbols,
+            adjacent_numbers,
+        })
+    }
+
+    /// The numbers adjacent to at least one symbol.
+    pub fn part_numbers(&self) -> impl Iterator<Item = &NumberSpan> {
+        let mut is_part_number = vec![false; self.numbers.len()];
+        for &number in self.adjacent_numbers.iter().flatten() {
+            is_part_number[number] = true;
+        }
 
-    for digit_index in 0..amount_of_digits {
-        n = n + digits[digit_index] * 10_u32.pow(amount_of_digits as u32 - digit_index as u32 - 1);
+        self.numbers
+            .iter()
+            .zip(is_part_number)
+            .filter_map(|(number, is_part_number)| is_part_number.then_some(number))
     }
 
-    n
+    /// For every symbol of the given character that is adjacent to exactly `k` numbers, the
+    /// product of these numbers, or `None` if it does not fit in a u64.
+    pub fn ratios(
+        &self,
+        character: char,
+        k: usize,
+    ) -> impl Iterator<Item = (&Symbol, Option<u64>)> {
+        self.symbols
+            .iter()
+            .zip(self.adjacent_numbers.iter())
+            .filter(move |(symbol, adjacent)| symbol.character == character && adjacent.len() == k)
+            .map(|(symbol, adjacent)| {
+                let ratio = adjacent.iter().try_fold(1_u64, |ratio, &number| {
+                    ratio.checked_mul(self.numbers[number].value.into())
+                });
+
+                (symbol, ratio)
+            })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    struct TestCase {
-        input: &'static str,
-        expected_output_a: u32,
-        expected_output_b: u32,
+    use crate::grid::RaggedRowError;
+
+    #[test]
+    fn ratios() {
+        let schematic = Schematic::parse(
+            "2.3.4
+.*.#.
+5..67",
+        )
+        .expect("parsing schematic");
+
+        let ratios: Vec<(char, usize, usize, Option<u64>)> = schematic
+            .ratios('*', 3)
+            .map(|(symbol, ratio)| (symbol.character, symbol.row, symbol.column, ratio))
+            .collect();
+        assert_eq!(ratios, vec![('*', 1, 1, Some(2 * 3 * 5))]);
+
+        let ratios: Vec<Option<u64>> = schematic.ratios('#', 3).map(|(_, ratio)| ratio).collect();
+        assert_eq!(ratios, vec![Some(3 * 4 * 67)]);
+
+        assert_eq!(schematic.ratios('*', 2).count(), 0);
+
+        let spans: Vec<(u32, usize, usize, usize)> = schematic
+            .part_numbers()
+            .map(|number| (number.value, number.row, number.start, number.end))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                (2, 0, 0, 1),
+                (3, 0, 2, 3),
+                (4, 0, 4, 5),
+                (5, 2, 0, 1),
+                (67, 2, 3, 5)
+            ]
+        );
+
+        let schematic =
+            Schematic::parse("4000000000.4000000000\n..........*..........\n..........4000000000.")
+                .expect("parsing schematic");
+        let ratios: Vec<Option<u64>> = schematic.ratios('*', 3).map(|(_, ratio)| ratio).collect();
+        assert_eq!(ratios, vec![None]);
+
+        // The sums no longer fit in a u32.
+        let schematic = "4000000000.4000000000\n..........*..........";
+        assert_eq!(
+            puzzle_a(schematic).expect("solving puzzle a"),
+            8_000_000_000
+        );
+        assert_eq!(
+            puzzle_b(schematic).expect("solving puzzle b"),
+            16_000_000_000_000_000_000
+        );
     }
 
     #[test]
-    fn puzzle() {
-        let test_cases = vec![
-            TestCase {
-                input: "..12=..",
-                expected_output_a: 12,
-                expected_output_b: 0,
-            },
-            TestCase {
-                input: "..*34",
-                expected_output_a: 34,
-                expected_output_b: 0,
-            },
-            TestCase {
-                input: "123..321
-...*..2&",
-                expected_output_a: 446,
-                expected_output_b: 0,
-            },
-            TestCase {
-                input: "467..114..
-...*......
-..35..633.
-......#...
-617*......
-.....+.58.
-..592.....
-......755.
-...$.*....
-.664.598..",
-                expected_output_a: 4361,
-                expected_output_b: 467835,
-            },
-        ];
-
-        for test_case in test_cases {
-            let output = puzzle_a(test_case.input).expect("calculation a failed");
-            assert_eq!(
-                output, test_case.expected_output_a,
-                "input: {:?}",
-                test_case.input
-            );
-            let output = puzzle_b(test_case.input).expect("calculation b failed");
-            assert_eq!(
-                output, test_case.expected_output_b,
-                "input: {:?}",
-                test_case.input
-            );
-        }
+    fn line_endings_and_characters() {
+        let schematic = "467..114..\r\n...€......\r\n..35..633.\r\n";
+        assert_eq!(puzzle_a(schematic).expect("calculation a failed"), 467 + 35);
+
+        let schematic = "12..\r..é3\r....";
+        assert_eq!(puzzle_a(schematic).expect("calculation a failed"), 12 + 3);
+
+        let schematic = "ü..\n.*.\n12.";
+        assert_eq!(puzzle_a(schematic).expect("calculation a failed"), 12);
+
+        let error = Schematic::parse("467..\n...*..\n..35.").err();
+        assert_eq!(
+            error.as_ref().and_then(|error| error.downcast_ref()),
+            Some(&RaggedRowError {
+                line_number: 2,
+                width: 6,
+                expected_width: 5,
+            })
+        );
+        assert!(puzzle_a("12\n\n*3").is_err());
+        assert!(puzzle_a("*99999999999").is_err());
     }
 }
@@ -1,82 +1,106 @@
 use std::collections::HashSet;
 
-pub fn solution(data: &str) -> Result<(u32, u32), Box<dyn std::error::Error>> {
-    let sum_a = puzzle_a(&data)?;
-    let sum_b = puzzle_b(&data)?;
+use crate::progress::Progress;
 
-    Ok((sum_a, sum_b))
+pub fn solution(
+    data: &str,
+    _progress: &mut dyn Progress,
+) -> Result<(i128, i128), Box<dyn std::error::Error>> {
+    let reports = card_reports(data)?;
+
+    Ok((
+        total_points(&reports)?.into(),
+        total_copies(&reports)?.into(),
+    ))
 }
 
 type CardNumber = u32;
 
-struct ScratchCard {
-    _card_number: CardNumber,
-    winning_numbers: HashSet<u32>,
-    drawn_numbers: HashSet<u32>,
-}
-
-fn puzzle_a(data: &str) -> Result<u32, Box<dyn std::error::Error>> {
-    let sum: u32 = data
-        .lines()
-        .map(|line| parse_scratch_card(line))
-        .map(|scratch_card| calculate_winning_matches(&scratch_card))
-        .map(|winning_matches| calculate_points(winning_matches))
-        .sum();
-
-    Ok(sum)
-}
-
-fn parse_scratch_card(line: &str) -> ScratchCard {
-    let card_prefix = "Card ";
-    let mut parser = line[card_prefix.len()..].split_terminator(": ");
-    let card_number: u32 = parser
-        .next()
-        .expect("parse error: no colon in line")
-        .trim()
-        .parse()
-        .expect("parse error: id is not a u32");
-
-    let mut numbers_parser = parser
-        .next()
-        .expect("parse error: no winning numbers")
-        .trim()
-        .split_terminator(" | ");
-
-    let winning_numbers_str = numbers_parser
-        .next()
-        .expect("parse error: no vertical bar found")
-        .trim();
-    let winning_numbers_list = string_to_num_list(winning_numbers_str);
-
-    let mut winning_numbers = HashSet::new();
-    winning_numbers_list.iter().for_each(|number| {
-        let _ = winning_numbers.insert(*number);
-    });
-
-    let drawn_numbers_str = numbers_parser
-        .next()
-        .expect("parse error: no drawn numbers found")
-        .trim();
-    let drawn_numbers_list = string_to_num_list(drawn_numbers_str);
-
-    let mut drawn_numbers = HashSet::new();
-    drawn_numbers_list.iter().for_each(|number| {
-        let _ = drawn_numbers.insert(*number);
-    });
-
-    ScratchCard {
-        _card_number: card_number,
-        winning_numbers,
-        drawn_numbers,
+pub struct ScratchCard {
+    pub card_number: CardNumber,
+    pub winning_numbers: HashSet<u32>,
+    pub drawn_numbers: HashSet<u32>,
+}
+
+/// How a single card played out: its matching numbers, the points they are worth, and how many
+/// copies of the card are held once all won copies are counted, including the original.
+#[derive(PartialEq, Debug)]
+pub struct CardReport {
+    pub card_number: CardNumber,
+    pub matches: u32,
+    pub points: u64,
+    pub copies: u64,
+}
+
+#[derive(PartialEq, Debug)]
+pub struct NonContiguousCardError {
+    pub line_number: usize,
+    pub card_number: CardNumber,
+    pub expected_card_number: CardNumber,
+}
+
+impl std::fmt::Display for NonContiguousCardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}: found card {}, expected card {}",
+            self.line_number, self.card_number, self.expected_card_number
+        )
     }
 }
 
-fn string_to_num_list(number_string: &str) -> Vec<u32> {
+impl std::error::Error for NonContiguousCardError {}
+
+pub fn puzzle_a(data: &str) -> Result<u64, Box<dyn std::error::Error>> {
+    Ok(total_points(&card_reports(data)?)?)
+}
+
+fn total_points(reports: &[CardReport]) -> Result<u64, String> {
+    reports
+        .iter()
+        .try_fold(0_u64, |sum, report| sum.checked_add(report.points))
+        .ok_or("overflow: too many points".to_string())
+}
+
+/// Parses every line of the data into a scratch card.
+pub fn parse_scratch_cards(data: &str) -> Result<Vec<ScratchCard>, String> {
+    data.lines()
+        .enumerate()
+        .map(|(index, line)| {
+            parse_scratch_card(line).map_err(|error| format!("line {}: {}", index + 1, error))
+        })
+        .collect()
+}
+
+/// Parses a line such as `Card 1: 41 48 83 | 83 86 6`.
+fn parse_scratch_card(line: &str) -> Result<ScratchCard, String> {
+    let (card, numbers) = line
+        .split_once(':')
+        .ok_or_else(|| format!("parse error: no colon in card {:?}", line))?;
+
+    let card_number: CardNumber = card
+        .strip_prefix("Card ")
+        .and_then(|card_number| card_number.trim().parse().ok())
+        .ok_or_else(|| format!("parse error: no card number in {:?}", card))?;
+
+    let (winning_numbers_str, drawn_numbers_str) = numbers
+        .split_once('|')
+        .ok_or_else(|| format!("parse error: no vertical bar in card {:?}", line))?;
+
+    Ok(ScratchCard {
+        card_number,
+        winning_numbers: string_to_num_set(winning_numbers_str)?,
+        drawn_numbers: string_to_num_set(drawn_numbers_str)?,
+    })
+}
+
+fn string_to_num_set(number_string: &str) -> Result<HashSet<u32>, String> {
     number_string
         .split_whitespace()
-        .map(|n| {
-            n.parse::<u32>()
-                .expect("parse error: non-u32 char in number list")
+        .map(|number| {
+            number
+                .parse::<u32>()
+                .map_err(|_| format!("parse error: {:?} is not a number", number))
         })
         .collect()
 }
@@ -88,89 +112,181 @@ fn calculate_winning_matches(card: &ScratchCard) -> u32 {
         .count() as u32
 }
 
-fn calculate_points(winning_matches: u32) -> u32 {
+/// The points of a card, or `None` if they do not fit in a u64.
+fn calculate_points(winning_matches: u32) -> Option<u64> {
     if winning_matches == 0 {
-        return 0;
+        return Some(0);
     }
-    let base: u32 = 2;
+    let base: u64 = 2;
 
-    base.pow(winning_matches - 1)
+    base.checked_pow(winning_matches - 1)
 }
 
-fn puzzle_b(data: &str) -> Result<u32, Box<dyn std::error::Error>> {
-    let mut card_copies: Vec<u32> = data.lines().map(|_| 1).collect();
+pub fn puzzle_b(data: &str) -> Result<u64, Box<dyn std::error::Error>> {
+    Ok(total_copies(&card_reports(data)?)?)
+}
 
-    let sum: u32 = data
-        .lines()
-        .map(|line| parse_scratch_card(line))
-        .enumerate()
-        .map(|(index, scratch_card)| {
-            let copies = card_copies[index];
-            let to_add = calculate_winning_matches(&scratch_card);
-            for j in 1..=to_add {
-                match card_copies.iter().nth(index + j as usize) {
-                    Some(_) => {
-                        card_copies[index + j as usize] = card_copies[index + j as usize] + copies;
-                    }
-                    None => break,
+fn total_copies(reports: &[CardReport]) -> Result<u64, String> {
+    reports
+        .iter()
+        .try_fold(0_u64, |sum, report| sum.checked_add(report.copies))
+        .ok_or("overflow: too many scratchcards".to_string())
+}
+
+/// Plays the cards in order. A card with `n` matches wins a copy of each of the next `n` cards
+/// for every copy held of it, so the copies it hands out are added at the first card they reach
+/// and dropped again after the last, keeping the cascade linear in the amount of cards. Cards
+/// have to be numbered one after the other, since copies are won by card number.
+pub fn card_reports(data: &str) -> Result<Vec<CardReport>, Box<dyn std::error::Error>> {
+    let scratch_cards = parse_scratch_cards(data)?;
+
+    if let Some(first_card) = scratch_cards.first() {
+        for (index, scratch_card) in scratch_cards.iter().enumerate() {
+            let expected_card_number = u32::try_from(index)
+                .ok()
+                .and_then(|offset| first_card.card_number.checked_add(offset));
+            if Some(scratch_card.card_number) != expected_card_number {
+                return Err(NonContiguousCardError {
+                    line_number: index + 1,
+                    card_number: scratch_card.card_number,
+                    expected_card_number: expected_card_number.unwrap_or(CardNumber::MAX),
                 }
+                .into());
             }
+        }
+    }
 
-            copies
-        })
-        .sum();
+    let mut copies_arriving = vec![0_u64; scratch_cards.len() + 1];
+    let mut copies_leaving = vec![0_u64; scratch_cards.len() + 1];
+    let mut copies_won: u64 = 0;
+    let mut reports = vec![];
+
+    for (index, scratch_card) in scratch_cards.iter().enumerate() {
+        copies_won = (copies_won - copies_leaving[index])
+            .checked_add(copies_arriving[index])
+            .ok_or("overflow: too many scratchcards")?;
+        let copies = copies_won
+            .checked_add(1)
+            .ok_or("overflow: too many scratchcards")?;
 
-    Ok(sum)
+        let matches = calculate_winning_matches(scratch_card);
+        // Copies are never won past the last card.
+        let first_won = index + 1;
+        let last_won = (index + matches as usize).min(scratch_cards.len() - 1);
+        if first_won <= last_won {
+            copies_arriving[first_won] = copies_arriving[first_won]
+                .checked_add(copies)
+                .ok_or("overflow: too many scratchcards")?;
+            copies_leaving[last_won + 1] += copies;
+        }
+
+        reports.push(CardReport {
+            card_number: scratch_card.card_number,
+            matches,
+            points: calculate_points(matches).ok_or_else(|| {
+                format!(
+                    "overflow: card {} with {} matches is worth too many points",
+                    scratch_card.card_number, matches
+                )
+            })?,
+            copies,
+        });
+    }
+
+    Ok(reports)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    struct TestCase {
-        input: String,
-        expected_output_a: u32,
-        expected_output_b: u32,
+    #[test]
+    fn card_breakdown() {
+        let input = "Card 3: 1 2 3 | 1 2 9
+Card 4: 5 6 | 5 7
+Card 5: 8 | 8
+Card 6: 4 | 9";
+
+        let reports = card_reports(input).expect("playing cards");
+        let expected_reports = vec![(3, 2, 2, 1), (4, 1, 1, 2), (5, 1, 1, 4), (6, 0, 0, 5)];
+        let reports: Vec<(CardNumber, u32, u64, u64)> = reports
+            .iter()
+            .map(|report| {
+                (
+                    report.card_number,
+                    report.matches,
+                    report.points,
+                    report.copies,
+                )
+            })
+            .collect();
+        assert_eq!(reports, expected_reports);
+
+        // The last card's matches would reach past the end of the table.
+        let reports = card_reports("Card 1: 1 2 | 1 2").expect("playing a single card");
+        assert_eq!(reports[0].copies, 1);
     }
 
     #[test]
-    fn puzzle() {
+    fn non_contiguous_cards() {
+        let input = "Card 1: 1 | 1
+Card 2: 2 | 2
+Card 4: 3 | 3";
+
+        let error = card_reports(input).expect_err("card 3 is missing");
+        let error = error
+            .downcast_ref::<NonContiguousCardError>()
+            .expect("non-contiguous card error");
+        assert_eq!(
+            *error,
+            NonContiguousCardError {
+                line_number: 3,
+                card_number: 4,
+                expected_card_number: 3,
+            }
+        );
+        assert!(puzzle_a(input).is_err());
+        assert!(puzzle_b(input).is_err());
+    }
+
+    #[test]
+    fn malformed_cards() {
         let test_cases = vec![
-            TestCase {
-                input: "".into(),
-                expected_output_a: 0,
-                expected_output_b: 0,
-            },
-            TestCase {
-                input: "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53".into(),
-                expected_output_a: 8,
-                expected_output_b: 1,
-            },
-            TestCase {
-                input: "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
-Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
-Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
-Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
-Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
-Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"
-                    .into(),
-                expected_output_a: 13,
-                expected_output_b: 30,
-            },
+            "Card 1 41 48 | 83 86",
+            "Cart 1: 41 48 | 83 86",
+            "Card one: 41 48 | 83 86",
+            "Card 1: 41 48 83 86",
+            "Card 1: 41 -48 | 83 86",
+            "Card 1: 1 | 1\n\nCard 2: 2 | 2",
+            "€",
         ];
-        for test_case in test_cases {
-            let output = puzzle_a(&test_case.input).expect("could not solve puzzle a");
-            assert_eq!(
-                output, test_case.expected_output_a,
-                "input: {}",
-                test_case.input
-            );
-            let output = puzzle_b(&test_case.input).expect("could not solve puzzle b");
-            assert_eq!(
-                output, test_case.expected_output_b,
-                "input: {}",
-                test_case.input
-            );
+
+        for input in test_cases {
+            assert!(parse_scratch_cards(input).is_err(), "input: {:?}", input);
+            assert!(puzzle_a(input).is_err(), "input: {:?}", input);
         }
+
+        let cards = parse_scratch_cards("Card  7:1 2|2 3").expect("parsing tightly spaced card");
+        assert_eq!(cards[0].card_number, 7);
+        assert_eq!(cards[0].winning_numbers, HashSet::from([1, 2]));
+        assert_eq!(cards[0].drawn_numbers, HashSet::from([2, 3]));
+    }
+
+    #[test]
+    fn many_matches() {
+        let numbers: Vec<String> = (1..=64).map(|number| number.to_string()).collect();
+        let card = format!("Card 1: {} | {}", numbers.join(" "), numbers.join(" "));
+        assert_eq!(puzzle_a(&card).expect("solving puzzle a"), 1 << 63);
+
+        let numbers: Vec<String> = (1..=65).map(|number| number.to_string()).collect();
+        let card = format!("Card 1: {} | {}", numbers.join(" "), numbers.join(" "));
+        assert!(puzzle_a(&card).is_err());
+
+        let numbers: Vec<String> = (1..=40).map(|number| number.to_string()).collect();
+        let card = format!("Card 1: {} | {}", numbers.join(" "), numbers.join(" "));
+        let cards = format!("{}\n{}", card, card.replacen("Card 1", "Card 2", 1));
+        let reports = card_reports(&cards).expect("playing cards");
+        assert_eq!(reports[0].points, 1 << 39);
+        assert_eq!(reports[1].copies, 2);
     }
 }
@@ -1,59 +1,184 @@
-use rayon::prelude::*;
+use std::collections::HashMap;
 
-pub fn solution(data: &str) -> Result<(u32, u32), Box<dyn std::error::Error>> {
-    let sum_a = puzzle_a(&data)?;
-    // Uncomment this to allow the expensive computation.
-    // let sum_b = puzzle_b(&data)?;
-    let sum_b = 137516820;
+use crate::interval::{Interval, IntervalSet};
+use crate::progress::Progress;
 
-    Ok((sum_a, sum_b))
+pub fn solution(
+    data: &str,
+    _progress: &mut dyn Progress,
+) -> Result<(i128, i128), Box<dyn std::error::Error>> {
+    let sum_a = puzzle_a(data)?;
+    let sum_b = puzzle_b(data)?;
+
+    Ok((sum_a.into(), sum_b.into()))
 }
 
+const SEED: &str = "seed";
+const LOCATION: &str = "location";
+
+#[derive(Clone, Debug)]
 struct RangeMap {
     source_start: u64,
     destination_start: u64,
     range_length: u64,
 }
 
-fn extract_seeds(data: &str) -> Vec<u64> {
-    data.lines()
-        .next()
-        .expect("data is empty")
-        .split_whitespace()
-        .skip(1)
-        .map(|s| s.parse::<u64>().expect("seed number is not a number"))
-        .collect()
+impl RangeMap {
+    fn source(&self) -> Interval<u64> {
+        Interval::new(self.source_start, self.source_start + self.range_length)
+    }
+
+    /// Shifts numbers within the source interval to the destination interval.
+    fn shift(&self, interval: Interval<u64>) -> Interval<u64> {
+        let offset = interval.start - self.source_start;
+        let destination_start = self.destination_start + offset;
+
+        Interval::new(
+            destination_start,
+            destination_start + (interval.end - interval.start),
+        )
+    }
+
+    fn inverse(&self) -> RangeMap {
+        RangeMap {
+            source_start: self.destination_start,
+            destination_start: self.source_start,
+            range_length: self.range_length,
+        }
+    }
 }
 
-fn extract_seeds_by_ranges(data: &str) -> Vec<u64> {
-    let seed_values = data
-        .lines()
-        .next()
-        .expect("data is empty")
-        .split_whitespace()
-        .skip(1);
+/// The range maps that convert numbers of the source category to numbers of the destination
+/// category, as listed under a `source-to-destination map:` header.
+struct CategoryMap {
+    source: String,
+    destination: String,
+    range_maps: Vec<RangeMap>,
+}
 
-    let seed_ranges = seed_values.clone().skip(1);
+/// The seeds to plant, and the maps between categories such as `seed` and `soil`.
+pub struct Almanac {
+    pub seeds: Vec<u64>,
+    category_maps: Vec<CategoryMap>,
+}
 
-    seed_values
-        .zip(seed_ranges)
-        .step_by(2)
-        .map(|(value, range)| {
-            let number = value.parse::<u64>().expect("seed number is not a number");
-            let range = range.parse::<u64>().expect("seed range is not a number");
-            let seed_range: Vec<u64> = (number..(number + range)).collect();
+impl Almanac {
+    /// Every category that is mapped from or to, in the order in which they are first listed.
+    pub fn categories(&self) -> Vec<&str> {
+        let mut categories: Vec<&str> = vec![];
+        for category_map in self.category_maps.iter() {
+            for category in [&category_map.source, &category_map.destination] {
+                if !categories.contains(&category.as_str()) {
+                    categories.push(category);
+                }
+            }
+        }
 
-            seed_range
+        categories
+    }
+
+    /// Builds the pipeline that converts numbers of one category to another, by following the
+    /// chain of category maps. If the chain only leads from the destination to the source, the
+    /// pipeline follows it in reverse with every range map inverted. Inverse lookups assume that
+    /// each layer maps its numbers one-to-one, which holds for the almanac's maps.
+    pub fn pipeline(&self, source: &str, destination: &str) -> Result<Pipeline, String> {
+        if let Some(chain) = self.chain(source, destination) {
+            let layers = chain
+                .into_iter()
+                .map(|category_map| category_map.range_maps.clone())
+                .collect();
+
+            return Ok(Pipeline { layers });
+        }
+
+        if let Some(chain) = self.chain(destination, source) {
+            let layers = chain
+                .into_iter()
+                .rev()
+                .map(|category_map| {
+                    category_map
+                        .range_maps
+                        .iter()
+                        .map(RangeMap::inverse)
+                        .collect()
+                })
+                .collect();
+
+            return Ok(Pipeline { layers });
+        }
+
+        Err(format!(
+            "no chain of maps from category {:?} to {:?}",
+            source, destination
+        ))
+    }
+
+    fn chain(&self, source: &str, destination: &str) -> Option<Vec<&CategoryMap>> {
+        let maps_by_source: HashMap<&str, &CategoryMap> = self
+            .category_maps
+            .iter()
+            .map(|category_map| (category_map.source.as_str(), category_map))
+            .collect();
+
+        let mut chain = vec![];
+        let mut cursor = source;
+
+        while cursor != destination {
+            // A chain longer than the amount of maps has to revisit a category.
+            if chain.len() == self.category_maps.len() {
+                return None;
+            }
+
+            let category_map = maps_by_source.get(cursor)?;
+            chain.push(*category_map);
+            cursor = &category_map.destination;
+        }
+
+        Some(chain)
+    }
+}
+
+/// Layers of range maps that convert numbers in order, each layer taking the output of the one
+/// before it.
+pub struct Pipeline {
+    layers: Vec<Vec<RangeMap>>,
+}
+
+impl Pipeline {
+    pub fn map(&self, number: u64) -> u64 {
+        self.layers.iter().fold(number, |number, range_maps| {
+            map_by_range_vec(number, range_maps)
+        })
+    }
+
+    pub fn map_ranges(&self, ranges: IntervalSet<u64>) -> IntervalSet<u64> {
+        self.layers.iter().fold(ranges, |ranges, range_maps| {
+            map_ranges_by_range_vec(ranges, range_maps)
+        })
+    }
+}
+
+/// Reads the seeds as pairs of a start and a length. A seed without a length is ignored.
+pub fn extract_seed_ranges(seeds: &[u64]) -> Result<IntervalSet<u64>, String> {
+    seeds
+        .chunks_exact(2)
+        .map(|pair| {
+            let (start, length) = (pair[0], pair[1]);
+            let end = start.checked_add(length).ok_or_else(|| {
+                format!(
+                    "parse error: seed range {} {} does not fit in u64",
+                    start, length
+                )
+            })?;
+
+            Ok(Interval::new(start, end))
         })
-        .flatten()
         .collect()
 }
 
-fn map_by_range_vec(number: u64, range_maps: &Vec<RangeMap>) -> u64 {
+fn map_by_range_vec(number: u64, range_maps: &[RangeMap]) -> u64 {
     for range_map in range_maps.iter() {
-        if number >= range_map.source_start
-            && number < range_map.source_start + range_map.range_length
-        {
+        if range_map.source().contains(number) {
             return range_map.destination_start + (number - range_map.source_start);
         }
     }
@@ -61,88 +186,139 @@ fn map_by_range_vec(number: u64, range_maps: &Vec<RangeMap>) -> u64 {
     number
 }
 
-fn extract_mappings(data: &str) -> Vec<Vec<RangeMap>> {
-    data.split_terminator("\n\n")
-        .skip(1)
-        .map(|range_map_list| {
-            range_map_list
-                .lines()
-                .skip(1)
-                .map(|source_map_range_str| {
-                    let mut range_iter = source_map_range_str.split_whitespace();
-                    let destination_start = range_iter
-                        .next()
-                        .expect("no destination start found")
-                        .parse::<u64>()
-                        .expect("parse error: destination start");
-
-                    let source_start = range_iter
-                        .next()
-                        .expect("no source start found")
-                        .parse::<u64>()
-                        .expect("parse error: source start");
-
-                    let range_length = range_iter
-                        .next()
-                        .expect("no range length found")
-                        .parse::<u64>()
-                        .expect("parse error: range length");
-
-                    RangeMap {
+/// Maps every range of numbers through the range maps of a single layer. Ranges are split at the
+/// boundaries of the range maps, so that each resulting piece is shifted by one range map only.
+/// Parts of ranges not covered by any range map are kept as they are.
+fn map_ranges_by_range_vec(ranges: IntervalSet<u64>, range_maps: &[RangeMap]) -> IntervalSet<u64> {
+    let mut mapped = vec![];
+    let mut unmapped = ranges;
+
+    for range_map in range_maps.iter() {
+        let source = IntervalSet::from_iter([range_map.source()]);
+
+        mapped.extend(
+            unmapped
+                .intersection(&source)
+                .intervals()
+                .iter()
+                .map(|&overlap| range_map.shift(overlap)),
+        );
+        unmapped = unmapped.difference(&source);
+    }
+
+    mapped.extend(unmapped.intervals());
+    mapped.into_iter().collect()
+}
+
+/// Parses the seeds on the first line and the category maps in the sections below it.
+pub fn extract_almanac(data: &str) -> Result<Almanac, Box<dyn std::error::Error>> {
+    let mut sections = data.split_terminator("\n\n");
+
+    let seeds = sections
+        .next()
+        .ok_or("data is empty")?
+        .trim()
+        .strip_prefix("seeds:")
+        .ok_or("parse error: no seeds found")?
+        .split_whitespace()
+        .map(|seed| seed.parse::<u64>())
+        .collect::<Result<Vec<u64>, _>>()?;
+
+    let category_maps = sections
+        .map(extract_category_map)
+        .collect::<Result<Vec<CategoryMap>, _>>()?;
+
+    Ok(Almanac {
+        seeds,
+        category_maps,
+    })
+}
+
+fn extract_category_map(section: &str) -> Result<CategoryMap, Box<dyn std::error::Error>> {
+    let mut lines = section.trim().lines();
+
+    let header = lines.next().ok_or("parse error: empty map section")?;
+    let (source, destination) = header
+        .strip_suffix(" map:")
+        .and_then(|categories| categories.split_once("-to-"))
+        .ok_or_else(|| format!("parse error: invalid map header {:?}", header))?;
+
+    let range_maps = lines
+        .map(|source_map_range_str| {
+            let numbers = source_map_range_str
+                .split_whitespace()
+                .map(|number| number.parse::<u64>())
+                .collect::<Result<Vec<u64>, _>>()?;
+
+            match numbers[..] {
+                // Both ends have to fit, so that the range map can be shifted and inverted.
+                [destination_start, source_start, range_length]
+                    if source_start.checked_add(range_length).is_some()
+                        && destination_start.checked_add(range_length).is_some() =>
+                {
+                    Ok(RangeMap {
                         source_start,
                         destination_start,
                         range_length,
-                    }
-                })
-                .collect()
+                    })
+                }
+                [_, _, _] => Err(format!(
+                    "parse error: range {:?} does not fit in u64",
+                    source_map_range_str
+                )
+                .into()),
+                _ => Err(format!(
+                    "parse error: expected three numbers in range {:?}",
+                    source_map_range_str
+                )
+                .into()),
+            }
         })
-        .collect()
-}
+        .collect::<Result<Vec<RangeMap>, Box<dyn std::error::Error>>>()?;
 
-fn seed_to_soil_min(seed_numbers: Vec<u64>, mappings: Vec<Vec<RangeMap>>) -> u64 {
-    seed_numbers
-        .iter()
-        .map(|seed| map_by_range_vec(*seed, &mappings[0]))
-        .map(|seed| map_by_range_vec(seed, &mappings[1]))
-        .map(|seed| map_by_range_vec(seed, &mappings[2]))
-        .map(|seed| map_by_range_vec(seed, &mappings[3]))
-        .map(|seed| map_by_range_vec(seed, &mappings[4]))
-        .map(|seed| map_by_range_vec(seed, &mappings[5]))
-        .map(|seed| map_by_range_vec(seed, &mappings[6]))
-        .min()
-        .expect("no soil number found")
+    Ok(CategoryMap {
+        source: source.to_string(),
+        destination: destination.to_string(),
+        range_maps,
+    })
 }
 
-fn puzzle_a(data: &str) -> Result<u32, Box<dyn std::error::Error>> {
-    let seed_numbers = extract_seeds(data);
+pub fn puzzle_a(data: &str) -> Result<u64, Box<dyn std::error::Error>> {
+    let almanac = extract_almanac(data)?;
+    let pipeline = almanac.pipeline(SEED, LOCATION)?;
 
-    let mappings: Vec<Vec<RangeMap>> = extract_mappings(data);
+    let location_min = almanac
+        .seeds
+        .iter()
+        .map(|seed| pipeline.map(*seed))
+        .min()
+        .ok_or("no location number found")?;
 
-    Ok(seed_to_soil_min(seed_numbers, mappings) as u32)
+    Ok(location_min)
 }
 
-fn puzzle_b(data: &str) -> Result<u32, Box<dyn std::error::Error>> {
-    let seed_numbers = extract_seeds_by_ranges(data);
+pub fn puzzle_b(data: &str) -> Result<u64, Box<dyn std::error::Error>> {
+    let almanac = extract_almanac(data)?;
+    let pipeline = almanac.pipeline(SEED, LOCATION)?;
 
-    let mappings: Vec<Vec<RangeMap>> = extract_mappings(data);
+    // The ranges are ordered, so the first one starts at the lowest location.
+    let location_min = pipeline
+        .map_ranges(extract_seed_ranges(&almanac.seeds)?)
+        .intervals()
+        .first()
+        .map(|range| range.start)
+        .ok_or("no location number found")?;
 
-    Ok(seed_to_soil_min(seed_numbers, mappings) as u32)
+    Ok(location_min)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use proptest::prelude::*;
 
-    struct TestCase {
-        input: String,
-        expected_output_a: u32,
-        expected_output_b: u32,
-    }
+    use super::*;
 
-    #[test]
-    fn puzzle() {
-        let test_cases = vec![TestCase {
-            input: "seeds: 79 14 55 13
+    const EXAMPLE: &str = "seeds: 79 14 55 13
 
 seed-to-soil map:
 50 98 2
@@ -174,26 +350,130 @@ temperature-to-humidity map:
 
 humidity-to-location map:
 60 56 37
-56 93 4"
-                .into(),
-            expected_output_a: 35,
-            expected_output_b: 46,
-        }];
-
-        for test_case in test_cases {
-            let output = puzzle_a(&test_case.input).expect("solving puzzle a");
-            assert_eq!(
-                output, test_case.expected_output_a,
-                "input: {}",
-                test_case.input,
-            );
-
-            let output = puzzle_b(&test_case.input).expect("solving puzzle b");
-            assert_eq!(
-                output, test_case.expected_output_b,
-                "input: {}",
-                test_case.input,
-            );
+56 93 4";
+
+    #[test]
+    fn pipeline() {
+        let almanac = extract_almanac(EXAMPLE).expect("parsing almanac");
+
+        let seed_to_soil = almanac.pipeline("seed", "soil").expect("seed to soil");
+        assert_eq!(seed_to_soil.map(79), 81);
+
+        let soil_to_location = almanac
+            .pipeline("soil", "location")
+            .expect("soil to location");
+        assert_eq!(soil_to_location.map(81), 82);
+
+        let location_to_seed = almanac
+            .pipeline("location", "seed")
+            .expect("location to seed");
+        assert_eq!(location_to_seed.map(82), 79);
+        assert_eq!(location_to_seed.map(46), 82);
+
+        assert!(almanac.pipeline("seed", "colour").is_err());
+
+        assert_eq!(almanac.seeds, vec![79, 14, 55, 13]);
+        assert_eq!(
+            almanac.categories(),
+            vec![
+                "seed",
+                "soil",
+                "fertilizer",
+                "water",
+                "light",
+                "temperature",
+                "humidity",
+                "location"
+            ]
+        );
+
+        let seed_ranges = extract_seed_ranges(&almanac.seeds).expect("seed ranges");
+        let locations = almanac
+            .pipeline("seed", "location")
+            .expect("seed to location")
+            .map_ranges(seed_ranges);
+        assert_eq!(
+            locations.intervals().first().map(|range| range.start),
+            Some(46)
+        );
+    }
+
+    #[test]
+    fn fewer_layers() {
+        let input = "seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-location map:
+0 15 37
+37 52 2
+39 0 15";
+
+        assert_eq!(puzzle_a(input).expect("solving puzzle a"), 52);
+        assert_eq!(puzzle_b(input).expect("solving puzzle b"), 57);
+    }
+
+    /// Layers of range maps over small numbers, whose sources may overlap, touch or leave gaps.
+    fn pipeline_layers() -> impl Strategy<Value = Vec<Vec<RangeMap>>> {
+        let range_map = (0..40_u64, 0..40_u64, 0..12_u64).prop_map(
+            |(source_start, destination_start, range_length)| RangeMap {
+                source_start,
+                destination_start,
+                range_length,
+            },
+        );
+
+        prop::collection::vec(prop::collection::vec(range_map, 0..5), 0..4)
+    }
+
+    proptest! {
+        #[test]
+        fn range_mapping_matches_seed_mapping(
+            layers in pipeline_layers(),
+            seeds in prop::collection::vec(0..50_u64, 0..8),
+        ) {
+            let pipeline = Pipeline { layers };
+            let seed_ranges = extract_seed_ranges(&seeds).expect("small seed ranges");
+
+            let expected: IntervalSet<u64> = seed_ranges
+                .intervals()
+                .iter()
+                .flat_map(|range| range.start..range.end)
+                .map(|seed| pipeline.map(seed))
+                .map(|location| Interval::new(location, location + 1))
+                .collect();
+
+            prop_assert_eq!(pipeline.map_ranges(seed_ranges), expected);
         }
     }
+
+    #[test]
+    fn large_numbers() {
+        let unmapped_seed = "seeds: 5000000000 5000000001\n\nseed-to-location map:\n0 1 5";
+        assert_eq!(
+            puzzle_a(unmapped_seed).expect("solving puzzle a"),
+            5_000_000_000
+        );
+        assert_eq!(
+            puzzle_b(unmapped_seed).expect("solving puzzle b"),
+            5_000_000_000
+        );
+
+        let overflowing_seed_range =
+            "seeds: 18446744073709551615 5\n\nseed-to-location map:\n0 1 5";
+        assert!(puzzle_b(overflowing_seed_range).is_err());
+
+        let overflowing_source = "seeds: 1\n\nseed-to-location map:\n0 18446744073709551615 5";
+        assert!(puzzle_a(overflowing_source).is_err());
+
+        let overflowing_destination = "seeds: 1\n\nseed-to-location map:\n18446744073709551615 0 5";
+        assert!(puzzle_a(overflowing_destination).is_err());
+
+        let last_numbers =
+            "seeds: 18446744073709551610 5\n\nseed-to-location map:\n0 18446744073709551610 5";
+        assert_eq!(puzzle_a(last_numbers).expect("solving puzzle a"), 0);
+        assert_eq!(puzzle_b(last_numbers).expect("solving puzzle b"), 0);
+    }
 }
@@ -1,113 +1,281 @@
-pub fn solution(data: &str) -> Result<(u32, u32), Box<dyn std::error::Error>> {
-    let sum_a = puzzle_a(&data)?;
-    let sum_b = puzzle_b(&data)?;
+use crate::math;
+use crate::progress::Progress;
 
-    Ok((sum_a as u32, sum_b as u32))
+pub fn solution(
+    data: &str,
+    _progress: &mut dyn Progress,
+) -> Result<(i128, i128), Box<dyn std::error::Error>> {
+    let sum_a = puzzle_a(data)?;
+    let sum_b = puzzle_b(data)?;
+
+    Ok((i128::try_from(sum_a)?, i128::try_from(sum_b)?))
 }
 
-fn puzzle_a(data: &str) -> Result<u64, Box<dyn std::error::Error>> {
-    let races = extract_races(data);
+pub fn puzzle_a(data: &str) -> Result<u128, Box<dyn std::error::Error>> {
+    let races = extract_races(data)?;
 
-    let combinations_to_win: u64 = races
-        .iter()
-        .map(|x| compute_ways_to_beat_record(x))
-        .product();
+    let combinations_to_win =
+        races
+            .iter()
+            .map(compute_ways_to_beat_record)
+            .try_fold(1_u128, |product, ways| {
+                product
+                    .checked_mul(ways?)
+                    .ok_or("overflow: too many combinations to win".to_string())
+            })?;
 
-    Ok(combinations_to_win as u64)
+    Ok(combinations_to_win)
 }
 
-fn puzzle_b(data: &str) -> Result<u64, Box<dyn std::error::Error>> {
-    let race = extract_race(data);
+pub fn puzzle_b(data: &str) -> Result<u128, Box<dyn std::error::Error>> {
+    let race = extract_race(data)?;
 
-    Ok(compute_ways_to_beat_record(&race))
+    Ok(compute_ways_to_beat_record(&race)?)
 }
 
-struct Race {
-    time: u64,
-    distance: u64,
+pub struct Race {
+    pub time: u128,
+    pub distance: u128,
 }
 
-fn extract_races(data: &str) -> Vec<Race> {
-    let races_data: Vec<Vec<u64>> = data
-        .trim()
-        .lines()
-        .map(|line| {
-            line.split_whitespace()
-                .skip(1)
-                .map(|number| number.parse::<u64>().expect("data is not a number"))
-                .collect()
-        })
-        .collect();
-
-    races_data[0]
-        .iter()
-        .zip(races_data[1].iter())
-        .map(|(&time, &distance)| Race { time, distance })
-        .collect()
-}
+/// Parses the races from a `Time:` line and a `Distance:` line, each listing one number per race.
+pub fn extract_races(data: &str) -> Result<Vec<Race>, Box<dyn std::error::Error>> {
+    let (times, distances) = extract_race_lines(data)?;
+
+    let parse_numbers = |line: &str| {
+        line.split_whitespace()
+            .map(|number| number.parse::<u128>())
+            .collect::<Result<Vec<u128>, _>>()
+    };
+    let times = parse_numbers(times)?;
+    let distances = parse_numbers(distances)?;
 
-fn extract_race(data: &str) -> Race {
-    let race_data: Vec<u64> = data
-        .trim()
-        .lines()
-        .map(|line| {
-            line.split_terminator(':')
-                .skip(1)
-                .map(|line| line.replace(" ", ""))
-                .map(|number| number.parse::<u64>().expect("data is not a number"))
-                .next()
-                .expect("no number found")
-        })
-        .collect();
-
-    Race {
-        time: race_data[0],
-        distance: race_data[1],
+    if times.len() != distances.len() {
+        return Err(format!(
+            "parse error: {} times but {} distances",
+            times.len(),
+            distances.len()
+        )
+        .into());
     }
+
+    Ok(times
+        .into_iter()
+        .zip(distances)
+        .map(|(time, distance)| Race { time, distance })
+        .collect())
 }
 
-fn compute_ways_to_beat_record(race: &Race) -> u64 {
-    (0..race.time)
-        .filter(|milliseconds_waited| {
-            (race.time - milliseconds_waited) * milliseconds_waited > race.distance
-        })
-        .count() as u64
+/// Parses a single race, reading the numbers on each line as one number with the spaces left out.
+pub fn extract_race(data: &str) -> Result<Race, Box<dyn std::error::Error>> {
+    let (time, distance) = extract_race_lines(data)?;
+
+    let parse_number = |line: &str| line.replace(' ', "").parse::<u128>();
+
+    Ok(Race {
+        time: parse_number(time)?,
+        distance: parse_number(distance)?,
+    })
+}
+
+/// The numbers after the `Time:` and `Distance:` labels.
+fn extract_race_lines(data: &str) -> Result<(&str, &str), String> {
+    let mut lines = data.trim().lines();
+
+    let time = lines
+        .next()
+        .and_then(|line| line.trim().strip_prefix("Time:"))
+        .ok_or("parse error: no time line found")?;
+    let distance = lines
+        .next()
+        .and_then(|line| line.trim().strip_prefix("Distance:"))
+        .ok_or("parse error: no distance line found")?;
+
+    Ok((time, distance))
+}
+
+/// Holding the button for `h` milliseconds beats the record when `h * (time - h) > distance`. The
+/// distance is largest when holding for `half = time / 2`, and the winning hold times lie within
+/// `sqrt(best - distance)` of it, up to half a millisecond for odd times. Since `best` fits in a
+/// u128 for race times below 2^65, unlike `time^2`, the integer square root gives the first
+/// winning hold time up to one millisecond, which is corrected by checking its neighbours exactly.
+/// The winning hold times are symmetric around `time / 2`, so the last one follows from the first.
+fn compute_ways_to_beat_record(race: &Race) -> Result<u128, String> {
+    // Holds up to `half` travel at most `best`, so their distances cannot overflow.
+    let beats_record = |hold: u128| hold * (race.time - hold) > race.distance;
+
+    let half = race.time / 2;
+    let best = half.checked_mul(race.time - half).ok_or(format!(
+        "overflow: race time {} is too long, it must be below 2^65",
+        race.time
+    ))?;
+
+    let discriminant = match best.checked_sub(race.distance) {
+        Some(discriminant) if discriminant > 0 => discriminant,
+        // The record is at least the best possible distance.
+        _ => return Ok(0),
+    };
+
+    let mut first_winning_hold = half.saturating_sub(math::isqrt_u128(discriminant));
+    while first_winning_hold > 0 && beats_record(first_winning_hold - 1) {
+        first_winning_hold -= 1;
+    }
+    while first_winning_hold <= half && !beats_record(first_winning_hold) {
+        first_winning_hold += 1;
+    }
+
+    if first_winning_hold > half {
+        return Ok(0);
+    }
+
+    let last_winning_hold = race.time - first_winning_hold;
+
+    Ok(last_winning_hold - first_winning_hold + 1)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    struct TestCase {
-        input: String,
-        expected_output_a: u64,
-        expected_output_b: u64,
-    }
-
     #[test]
-    fn puzzle() {
-        let test_cases = vec![TestCase {
-            input: "Time:      7  15   30
-Distance:  9  40  200"
-                .into(),
-            expected_output_a: 288,
-            expected_output_b: 71503,
-        }];
-
-        for test_case in test_cases {
-            let output = puzzle_a(&test_case.input).expect("solving puzzle a");
+    fn boundaries() {
+        let test_cases = vec![
+            // The distance at the roots exactly equals the record.
+            (
+                Race {
+                    time: 30,
+                    distance: 200,
+                },
+                9,
+            ),
+            (
+                Race {
+                    time: 4,
+                    distance: 4,
+                },
+                0,
+            ),
+            (
+                Race {
+                    time: 4,
+                    distance: 3,
+                },
+                1,
+            ),
+            (
+                Race {
+                    time: 5,
+                    distance: 6,
+                },
+                0,
+            ),
+            (
+                Race {
+                    time: 5,
+                    distance: 5,
+                },
+                2,
+            ),
+            (
+                Race {
+                    time: 0,
+                    distance: 0,
+                },
+                0,
+            ),
+            (
+                Race {
+                    time: 7,
+                    distance: 0,
+                },
+                6,
+            ),
+            (
+                Race {
+                    time: 10_u128.pow(18),
+                    distance: 0,
+                },
+                10_u128.pow(18) - 1,
+            ),
+            (
+                Race {
+                    time: 10_u128.pow(19),
+                    distance: 25 * 10_u128.pow(36) - 1,
+                },
+                1,
+            ),
+            (
+                Race {
+                    time: 10_u128.pow(19),
+                    distance: 25 * 10_u128.pow(36),
+                },
+                0,
+            ),
+        ];
+
+        for (race, expected_ways) in test_cases {
+            let ways = compute_ways_to_beat_record(&race).expect("computing ways");
             assert_eq!(
-                output, test_case.expected_output_a,
-                "input: {}",
-                test_case.input,
+                ways, expected_ways,
+                "time: {}, distance: {}",
+                race.time, race.distance
             );
+        }
 
-            let output = puzzle_b(&test_case.input).expect("solving puzzle b");
+        let overflowing_race = Race {
+            time: u128::MAX,
+            distance: 0,
+        };
+        assert!(compute_ways_to_beat_record(&overflowing_race).is_err());
+
+        // The best distance of a race fits in a u128 for race times below 2^65.
+        let long_races = [
+            (1 << 64, 0, (1 << 64) - 1),
+            ((1 << 64) + 1, (1 << 126) + (1 << 63) - 1, 2),
+            ((1 << 65) - 1, 0, (1 << 65) - 2),
+            ((1 << 65) - 1, u128::MAX - (1 << 64) + 1, 0),
+        ];
+        for (time, distance, expected_ways) in long_races {
+            let ways = compute_ways_to_beat_record(&Race { time, distance });
             assert_eq!(
-                output, test_case.expected_output_b,
-                "input: {}",
-                test_case.input,
+                ways,
+                Ok(expected_ways),
+                "time: {}, distance: {}",
+                time,
+                distance
             );
         }
+        let too_long_race = Race {
+            time: 1 << 65,
+            distance: 0,
+        };
+        assert!(compute_ways_to_beat_record(&too_long_race).is_err());
+
+        let unbeatable_race = Race {
+            time: 10,
+            distance: u128::MAX,
+        };
+        assert_eq!(compute_ways_to_beat_record(&unbeatable_race), Ok(0));
+    }
+
+    #[test]
+    fn malformed_races() {
+        let test_cases = vec![
+            "",
+            "Time: 7 15",
+            "Time: 7 15\nDistance: 9",
+            "Time: 7 x\nDistance: 9 40",
+            "Distance: 9 40\nTime: 7 15",
+            "Time: 7 15\nDistance: 9 -40",
+            "Tíme: 7\nDistance: 9",
+        ];
+
+        for input in test_cases {
+            assert!(puzzle_a(input).is_err(), "input: {:?}", input);
+        }
+
+        assert!(extract_race("Time: 7 15\nDistance: 9 x").is_err());
+        let race = extract_race("Time: 7 15\nDistance: 9 40").expect("parsing race");
+        assert_eq!((race.time, race.distance), (715, 940));
     }
 }
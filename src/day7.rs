@@ -1,68 +1,355 @@
 use std::collections::HashMap;
-pub fn solution(data: &str) -> Result<(u32, u32), Box<dyn std::error::Error>> {
-    let sum_a = puzzle_a(&data)?;
-    let sum_b = puzzle_b(&data)?;
 
-    Ok((sum_a, sum_b))
+use crate::progress::Progress;
+
+pub fn solution(
+    data: &str,
+    _progress: &mut dyn Progress,
+) -> Result<(i128, i128), Box<dyn std::error::Error>> {
+    let sum_a = puzzle_a(data)?;
+    let sum_b = puzzle_b(data)?;
+
+    Ok((sum_a.into(), sum_b.into()))
 }
 
-fn puzzle_a(data: &str) -> Result<u32, Box<dyn std::error::Error>> {
-    let include_joker = false;
-    play_poker(data, include_joker)
+pub fn puzzle_a(data: &str) -> Result<u64, Box<dyn std::error::Error>> {
+    play_poker(data, &Rules::camel_cards())
 }
 
-fn puzzle_b(data: &str) -> Result<u32, Box<dyn std::error::Error>> {
-    let include_joker = true;
-    play_poker(data, include_joker)
+pub fn puzzle_b(data: &str) -> Result<u64, Box<dyn std::error::Error>> {
+    play_poker(data, &Rules::camel_cards_with_jokers())
 }
 
-fn play_poker(data: &str, include_joker: bool) -> Result<u32, Box<dyn std::error::Error>> {
-    let mut plays = extract_plays(data, include_joker)?;
+/// How hands are parsed, classified and ranked.
+pub struct Rules {
+    /// Card labels from weakest to strongest.
+    pub card_order: Vec<char>,
+    /// Cards that count as whichever card makes the hand type strongest.
+    pub wildcards: Vec<char>,
+    /// The amount of cards in every hand.
+    pub hand_size: usize,
+    pub tie_break: TieBreak,
+}
+
+/// How hands of the same type are ordered.
+pub enum TieBreak {
+    /// Compare the cards one by one, in the order they were dealt.
+    DealtOrder,
+    /// Compare the cards one by one, from the strongest to the weakest card in each hand.
+    HighestCard,
+}
+
+impl Rules {
+    pub fn camel_cards() -> Rules {
+        Rules {
+            card_order: "23456789TJQKA".chars().collect(),
+            wildcards: vec![],
+            hand_size: 5,
+            tie_break: TieBreak::DealtOrder,
+        }
+    }
+
+    pub fn camel_cards_with_jokers() -> Rules {
+        Rules {
+            card_order: "J23456789TQKA".chars().collect(),
+            wildcards: vec!['J'],
+            hand_size: 5,
+            tie_break: TieBreak::DealtOrder,
+        }
+    }
+
+    /// Checks that the strength of every card fits in a `u8`, and so does the amount of cards in a
+    /// hand, which is what groups of equal cards are counted in.
+    fn check(&self) -> Result<(), String> {
+        if self.card_order.len() > u8::MAX as usize + 1 {
+            return Err(format!(
+                "rules have {} card labels, but at most {} are supported",
+                self.card_order.len(),
+                u8::MAX as usize + 1
+            ));
+        }
+        if self.hand_size > u8::MAX as usize {
+            return Err(format!(
+                "rules have hands of {} cards, but at most {} are supported",
+                self.hand_size,
+                u8::MAX
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn card(&self, label: char) -> Option<Card> {
+        let strength = self.card_order.iter().position(|&card| card == label)?;
+
+        Some(Card {
+            strength: strength as u8,
+            label,
+        })
+    }
+
+    fn is_wildcard(&self, card: &Card) -> bool {
+        self.wildcards.contains(&card.label)
+    }
+
+    /// The amount of each card in the hand that is not a wildcard, and the amount of wildcards.
+    fn card_amounts<'a>(&self, hand: &'a [Card]) -> (HashMap<&'a Card, u8>, u8) {
+        let mut wildcards = 0;
+        let mut card_amounts: HashMap<&Card, u8> = HashMap::new();
+
+        for card in hand {
+            if self.is_wildcard(card) {
+                wildcards += 1;
+            } else {
+                *card_amounts.entry(card).or_insert(0) += 1;
+            }
+        }
+
+        (card_amounts, wildcards)
+    }
+
+    /// The amounts of equal cards in the hand, from the largest group to the smallest. Wildcards
+    /// join the largest group, which always gives the strongest hand type.
+    fn count_signature(&self, hand: &[Card]) -> Vec<u8> {
+        let (card_amounts, wildcards) = self.card_amounts(hand);
+
+        let mut signature: Vec<u8> = card_amounts.into_values().collect();
+        signature.sort_by(|a, b| b.cmp(a));
+
+        match signature.first_mut() {
+            Some(largest) => *largest += wildcards,
+            None => signature.push(wildcards),
+        }
+
+        signature
+    }
+
+    /// The card that the wildcards in the hand stand for: the strongest card of the largest group,
+    /// or the strongest card there is when the hand holds nothing but wildcards.
+    fn substitution(&self, hand: &[Card]) -> Option<char> {
+        let (card_amounts, wildcards) = self.card_amounts(hand);
+        if wildcards == 0 {
+            return None;
+        }
 
-    plays.sort_by(|play_a, play_b| {
-        let hand_type_a = derive_hand_type(&play_a.hand);
-        let hand_type_b = derive_hand_type(&play_b.hand);
-        match hand_type_a.cmp(&hand_type_b) {
-            std::cmp::Ordering::Less => std::cmp::Ordering::Less,
-            std::cmp::Ordering::Greater => std::cmp::Ordering::Greater,
-            _ => play_a.hand.cmp(&play_b.hand),
+        card_amounts
+            .into_iter()
+            .max_by(|(card_a, amount_a), (card_b, amount_b)| {
+                amount_a.cmp(amount_b).then(card_a.cmp(card_b))
+            })
+            .map(|(card, _)| card.label)
+            .or_else(|| {
+                self.card_order
+                    .iter()
+                    .rev()
+                    .find(|label| !self.wildcards.contains(label))
+                    .copied()
+            })
+    }
+
+    /// The cards in the order in which they are compared between hands of the same type.
+    fn tie_break_cards(&self, hand: &[Card]) -> Vec<Card> {
+        let mut cards = hand.to_vec();
+        match self.tie_break {
+            TieBreak::DealtOrder => {}
+            TieBreak::HighestCard => cards.sort_by(|a, b| b.cmp(a)),
         }
-    });
 
-    let sum = plays
+        cards
+    }
+
+    /// What hands are sorted by: the hand type, then the count signature, then the cards.
+    fn rank(&self, hand: &[Card]) -> Rank {
+        let signature = self.count_signature(hand);
+        let hand_type = HandType::from_count_signature(&signature);
+
+        (hand_type, signature, self.tie_break_cards(hand))
+    }
+}
+
+/// The total winnings of all plays, ranked by the given rules.
+pub fn play_poker(data: &str, rules: &Rules) -> Result<u64, Box<dyn std::error::Error>> {
+    let ranked_plays = rank_plays(data, rules)?;
+
+    let total = ranked_plays
         .iter()
+        .try_fold(0_u64, |total, ranked_play| {
+            total.checked_add(ranked_play.winnings)
+        })
+        .ok_or("overflow: total winnings do not fit in u64")?;
+
+    Ok(total)
+}
+
+/// A play together with everything that decided its winnings.
+struct RankedPlay {
+    hand: String,
+    bet: u32,
+    hand_type: HandType,
+    // The card that the wildcards in the hand stand for.
+    substitution: Option<char>,
+    rank: u64,
+    winnings: u64,
+}
+
+fn rank_plays(data: &str, rules: &Rules) -> Result<Vec<RankedPlay>, Box<dyn std::error::Error>> {
+    let plays = extract_plays(data, rules)?;
+
+    // Hands with more cards than a standard hand can have types beyond the named ones, such as
+    // three pairs. Larger groups of equal cards make stronger hands, so the count signature orders
+    // hands of the same named type.
+    let mut ranked_plays: Vec<(Rank, Play)> = plays
+        .into_iter()
+        .map(|play| (rules.rank(&play.hand), play))
+        .collect();
+
+    ranked_plays.sort_by(|(rank_a, _), (rank_b, _)| rank_a.cmp(rank_b));
+
+    ranked_plays
+        .into_iter()
         .enumerate()
-        .map(|(index, value)| (index as u32 + 1) * value.bet)
-        .sum();
+        .map(|(index, ((hand_type, _, _), play))| {
+            let rank = index as u64 + 1;
+            let winnings = rank
+                .checked_mul(play.bet.into())
+                .ok_or("overflow: winnings do not fit in u64")?;
 
-    Ok(sum)
+            Ok(RankedPlay {
+                hand: play.hand.iter().map(|card| card.label).collect(),
+                bet: play.bet,
+                hand_type,
+                substitution: rules.substitution(&play.hand),
+                rank,
+                winnings,
+            })
+        })
+        .collect()
 }
 
-#[derive(Eq, PartialEq, PartialOrd, Debug)]
-struct Play {
+pub enum ExplanationFormat {
+    Table,
+    Json,
+}
+
+impl std::str::FromStr for ExplanationFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "table" => Ok(ExplanationFormat::Table),
+            "json" => Ok(ExplanationFormat::Json),
+            _ => Err(format!("unknown explanation format {:?}", format)),
+        }
+    }
+}
+
+/// Lists every play of both parts with its hand type, wildcard substitution, rank and winnings,
+/// to find out which hand was ranked differently than expected.
+pub fn explain(
+    data: &str,
+    format: &ExplanationFormat,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let parts = [
+        ("a", rank_plays(data, &Rules::camel_cards())?),
+        ("b", rank_plays(data, &Rules::camel_cards_with_jokers())?),
+    ];
+
+    let explanation = match format {
+        ExplanationFormat::Table => parts
+            .iter()
+            .map(|(part, ranked_plays)| {
+                let rows: String = ranked_plays
+                    .iter()
+                    .map(|ranked_play| {
+                        format!(
+                            "{:>5} {:<8} {:<13} {:>12} {:>6} {:>10}\n",
+                            ranked_play.rank,
+                            ranked_play.hand,
+                            format!("{:?}", ranked_play.hand_type),
+                            ranked_play
+                                .substitution
+                                .map_or("-".to_string(), |label| label.to_string()),
+                            ranked_play.bet,
+                            ranked_play.winnings,
+                        )
+                    })
+                    .collect();
+
+                format!(
+                    "7{}:\n{:>5} {:<8} {:<13} {:>12} {:>6} {:>10}\n{}",
+                    part.to_uppercase(),
+                    "rank",
+                    "hand",
+                    "type",
+                    "substitution",
+                    "bet",
+                    "winnings",
+                    rows
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n"),
+        ExplanationFormat::Json => {
+            let parts_json: Vec<String> = parts
+                .iter()
+                .map(|(part, ranked_plays)| {
+                    let plays_json: Vec<String> = ranked_plays
+                        .iter()
+                        .map(|ranked_play| {
+                            format!(
+                                "{{\"rank\":{},\"hand\":{},\"hand_type\":\"{:?}\",\"substitution\":{},\"bet\":{},\"winnings\":{}}}",
+                                ranked_play.rank,
+                                json_string(&ranked_play.hand),
+                                ranked_play.hand_type,
+                                ranked_play
+                                    .substitution
+                                    .map_or("null".to_string(), |label| json_string(&label.to_string())),
+                                ranked_play.bet,
+                                ranked_play.winnings,
+                            )
+                        })
+                        .collect();
+
+                    format!("\"{}\":[{}]", part, plays_json.join(","))
+                })
+                .collect();
+
+            format!("{{{}}}\n", parts_json.join(","))
+        }
+    };
+
+    Ok(explanation)
+}
+
+fn json_string(value: &str) -> String {
+    let escaped: String = value
+        .chars()
+        .flat_map(|character| match character {
+            '"' => vec!['\\', '"'],
+            '\\' => vec!['\\', '\\'],
+            _ => vec![character],
+        })
+        .collect();
+
+    format!("\"{}\"", escaped)
+}
+
+type Rank = (HandType, Vec<u8>, Vec<Card>);
+
+#[derive(Eq, PartialEq, Debug)]
+pub struct Play {
     hand: Vec<Card>,
     bet: u32,
 }
 
-#[derive(Eq, PartialEq, PartialOrd, Ord, Hash, Debug)]
-enum Card {
-    Joker,
-    Two,
-    Three,
-    Four,
-    Five,
-    Six,
-    Seven,
-    Eight,
-    Nine,
-    Ten,
-    Jack,
-    Queen,
-    King,
-    Ace,
+/// A card's strength is its position in the rules' card order.
+#[derive(Eq, PartialEq, PartialOrd, Ord, Hash, Clone, Debug)]
+struct Card {
+    strength: u8,
+    label: char,
 }
 
-#[derive(Eq, PartialEq, PartialOrd, Ord, Debug)]
+#[derive(Eq, PartialEq, PartialOrd, Ord, Clone, Copy, Debug)]
 enum HandType {
     HighCard,
     OnePair,
@@ -73,200 +360,289 @@ enum HandType {
     FiveOfAKind,
 }
 
-fn derive_hand_type(hand: &[Card]) -> HandType {
-    let mut jokers = 0;
-    let mut hand_map: HashMap<&Card, u8> = HashMap::new();
-
-    hand.iter().for_each(|card| {
-        if *card == Card::Joker {
-            jokers += 1;
-        } else {
-            let card_amount = hand_map.entry(card).or_insert(0);
-            *card_amount += 1;
+impl HandType {
+    fn from_count_signature(signature: &[u8]) -> HandType {
+        match signature {
+            [largest, ..] if *largest >= 5 => HandType::FiveOfAKind,
+            [4, ..] => HandType::FourOfAKind,
+            [3, second, ..] if *second >= 2 => HandType::FullHouse,
+            [3, ..] => HandType::ThreeOfAKind,
+            [2, 2, ..] => HandType::TwoPair,
+            [2, ..] => HandType::OnePair,
+            _ => HandType::HighCard,
         }
-    });
-
-    if jokers == 5 {
-        return HandType::FiveOfAKind;
     }
-
-    let mut highest_amount: u8 = 0;
-    let mut second_highest_amount: u8 = 0;
-
-    hand_map.iter().for_each(|(_card, cards_amount)| {
-        if cards_amount > &highest_amount {
-            second_highest_amount = highest_amount;
-            highest_amount = *cards_amount;
-        } else if cards_amount > &second_highest_amount {
-            second_highest_amount = *cards_amount;
-        }
-    });
-
-    let hand_type = match highest_amount {
-        5 => HandType::FiveOfAKind,
-        4 => match jokers {
-            0 => HandType::FourOfAKind,
-            _ => HandType::FiveOfAKind,
-        },
-        3 => match jokers {
-            0 => match second_highest_amount {
-                0 => panic!("with 3 as highest_amount, cannot have 0 second_highest_amount"),
-                1 => HandType::ThreeOfAKind,
-                2 => HandType::FullHouse,
-                _ => panic!("second_highest_amount cannot be greater than highest amount"),
-            },
-            1 => HandType::FourOfAKind,
-            _ => HandType::FiveOfAKind,
-        },
-        2 => match jokers {
-            0 => match second_highest_amount {
-                0 => panic!("with 3 as highest_amount, cannot have 0 second_highest_amount"),
-                1 => HandType::OnePair,
-                2 => HandType::TwoPair,
-                _ => panic!("second_highest_amount cannot be greater than highest amount"),
-            },
-            1 => match second_highest_amount {
-                0 => panic!("with 3 as highest_amount, cannot have 0 second_highest_amount"),
-                1 => HandType::ThreeOfAKind,
-                2 => HandType::FullHouse,
-                _ => panic!("second_highest_amount cannot be greater than highest amount"),
-            },
-            2 => HandType::FourOfAKind,
-            _ => HandType::FiveOfAKind,
-        },
-        1 => match jokers {
-            0 => HandType::HighCard,
-            1 => HandType::OnePair,
-            2 => HandType::ThreeOfAKind,
-            3 => HandType::FourOfAKind,
-            _ => HandType::FiveOfAKind,
-        },
-        _ => HandType::HighCard,
-    };
-
-    hand_type
 }
 
-fn card_from_char(character: char, j_is_joker: bool) -> Card {
-    match character {
-        'A' => Card::Ace,
-        'K' => Card::King,
-        'Q' => Card::Queen,
-        'J' => match j_is_joker {
-            true => Card::Joker,
-            false => Card::Jack,
-        },
-        'T' => Card::Ten,
-        '9' => Card::Nine,
-        '8' => Card::Eight,
-        '7' => Card::Seven,
-        '6' => Card::Six,
-        '5' => Card::Five,
-        '4' => Card::Four,
-        '3' => Card::Three,
-        '2' => Card::Two,
-        _ => panic!("parse error: card could not be parsed"),
-    }
-}
+/// Parses a hand and a bet per line, with the cards the rules know of.
+pub fn extract_plays(data: &str, rules: &Rules) -> Result<Vec<Play>, Box<dyn std::error::Error>> {
+    rules.check()?;
 
-fn extract_plays(data: &str, j_is_joker: bool) -> Result<Vec<Play>, Box<dyn std::error::Error>> {
-    let plays = data
-        .trim()
+    data.trim()
         .lines()
         .map(|play| {
-            let mut play_iter = play.trim().split_whitespace();
+            let mut play_iter = play.split_whitespace();
 
-            if let (Some(hand_str), Some(bet_str)) = (play_iter.next(), play_iter.next()) {
-                let hand = hand_str
-                    .chars()
-                    .map(|card_character| card_from_char(card_character, j_is_joker))
-                    .collect();
+            let (Some(hand_str), Some(bet_str)) = (play_iter.next(), play_iter.next()) else {
+                return Err(format!("parse error: play {:?} could not be parsed", play).into());
+            };
 
-                let bet = bet_str.parse::<u32>().expect("bet amount is not a number");
+            let hand = hand_str
+                .chars()
+                .map(|label| {
+                    rules
+                        .card(label)
+                        .ok_or(format!("parse error: unknown card {:?}", label))
+                })
+                .collect::<Result<Vec<Card>, String>>()?;
 
-                Play { hand, bet }
-            } else {
-                panic!("parse error: play could not be parsed");
+            if hand.len() != rules.hand_size {
+                return Err(format!(
+                    "parse error: hand {:?} does not have {} cards",
+                    hand_str, rules.hand_size
+                )
+                .into());
             }
-        })
-        .collect();
 
-    Ok(plays)
+            let bet = bet_str.parse::<u32>()?;
+
+            Ok(Play { hand, bet })
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use super::*;
 
-    struct TestCase {
-        input: String,
-        expected_output_a: u32,
-        expected_output_b: u32,
+    #[test]
+    fn rules() {
+        let test_cases = vec![
+            // Kings beat queens when the cards are sorted, even though the queen is dealt first.
+            (
+                Rules {
+                    tie_break: TieBreak::HighestCard,
+                    ..Rules::camel_cards()
+                },
+                "Q2K34 1
+KQ234 2",
+                1 + 2 * 2,
+            ),
+            // Both twos and jokers are wild.
+            (
+                Rules {
+                    card_order: "2J3456789TQKA".chars().collect(),
+                    wildcards: vec!['2', 'J'],
+                    ..Rules::camel_cards_with_jokers()
+                },
+                "2JAAK 1
+AAAKK 2",
+                // The second hand is a full house, the first one four of a kind.
+                2 + 2,
+            ),
+            // In a hand of six cards, three pairs are two pair and two triples are a full house.
+            (
+                Rules {
+                    hand_size: 6,
+                    ..Rules::camel_cards()
+                },
+                "223344 1
+AAA234 2
+222333 3",
+                1 + 2 * 2 + 3 * 3,
+            ),
+        ];
+
+        for (rules, input, expected_output) in test_cases {
+            let output = play_poker(input, &rules).expect("playing poker");
+            assert_eq!(output, expected_output, "input: {}", input);
+        }
+
+        assert!(play_poker("2345 1", &Rules::camel_cards()).is_err());
+        assert!(play_poker("2345X 1", &Rules::camel_cards()).is_err());
     }
 
     #[test]
-    fn puzzle() {
+    fn limits() {
+        let output = puzzle_a("AAAAA 4294967295\nKKKKK 4294967295").expect("solving puzzle a");
+        assert_eq!(output, 3 * 4294967295);
+
+        let large_hands = Rules {
+            hand_size: 256,
+            ..Rules::camel_cards()
+        };
+        let hand = "A".repeat(256);
+        assert!(play_poker(&format!("{} 1", hand), &large_hands).is_err());
+
+        let largest_hands = Rules {
+            hand_size: 255,
+            ..Rules::camel_cards_with_jokers()
+        };
+        let hand = "J".repeat(255);
+        assert_eq!(
+            play_poker(&format!("{} 1", hand), &largest_hands).ok(),
+            Some(1)
+        );
+
+        let many_labels = Rules {
+            card_order: ('\u{100}'..'\u{201}').collect(),
+            ..Rules::camel_cards()
+        };
+        assert_eq!(many_labels.card_order.len(), 257);
+        assert!(play_poker("\u{100}\u{101}\u{102}\u{103}\u{104} 1", &many_labels).is_err());
+    }
+
+    #[test]
+    fn hand_types() {
+        let rules = Rules::camel_cards_with_jokers();
         let test_cases = vec![
-            TestCase {
-                input: "33456 10".into(),
-                expected_output_a: 10,
-                expected_output_b: 10,
-            },
-            TestCase {
-                input: "AA456 10
-            55567 20"
-                    .into(),
-                expected_output_a: 50,
-                expected_output_b: 50,
-            },
-            TestCase {
-                input: "33456 1
-            KKJ77 300
-            55567 20"
-                    .into(),
-                expected_output_a: 661,
-                expected_output_b: 941,
-            },
-            TestCase {
-                input: "KTJJT 20
-            QQQJA 300"
-                    .into(),
-                expected_output_a: 620,
-                expected_output_b: 340,
-            },
-            TestCase {
-                input: "T55J5 1
-KTJJT 20
-QQQJA 300"
-                    .into(),
-                expected_output_a: 922,
-                expected_output_b: 661,
-            },
-            TestCase {
-                input: "32T3K 765
-T55J5 684
-KK677 28
-KTJJT 220
-QQQJA 483"
-                    .into(),
-                expected_output_a: 6440,
-                expected_output_b: 5905,
-            },
+            ("JJJJJ", HandType::FiveOfAKind),
+            ("AJJJJ", HandType::FiveOfAKind),
+            ("AAJJK", HandType::FourOfAKind),
+            ("AAKKJ", HandType::FullHouse),
+            ("AKJJ2", HandType::ThreeOfAKind),
+            ("AAKK2", HandType::TwoPair),
+            ("AKQJ2", HandType::OnePair),
+            ("AKQT2", HandType::HighCard),
         ];
 
-        for test_case in test_cases {
-            let output = puzzle_a(&test_case.input).expect("solving puzzle a");
+        for (hand_str, expected_hand_type) in test_cases {
+            let hand: Vec<Card> = hand_str
+                .chars()
+                .map(|label| rules.card(label).expect("known card"))
+                .collect();
+            let signature = rules.count_signature(&hand);
             assert_eq!(
-                output, test_case.expected_output_a,
-                "puzzle a failed for input: {}",
-                test_case.input,
+                HandType::from_count_signature(&signature),
+                expected_hand_type,
+                "hand: {}",
+                hand_str
             );
+        }
+    }
 
-            let output = puzzle_b(&test_case.input).expect("solving puzzle b");
-            assert_eq!(
-                output, test_case.expected_output_b,
-                "puzzle b failed for input: {}",
-                test_case.input,
+    #[test]
+    fn explanation() {
+        let input = "T55J5 1
+KTJJT 20
+QQQJA 300";
+
+        let ranked_plays =
+            rank_plays(input, &Rules::camel_cards_with_jokers()).expect("ranking plays");
+        let summary: Vec<(&str, HandType, Option<char>, u64, u64)> = ranked_plays
+            .iter()
+            .map(|ranked_play| {
+                (
+                    ranked_play.hand.as_str(),
+                    ranked_play.hand_type,
+                    ranked_play.substitution,
+                    ranked_play.rank,
+                    ranked_play.winnings,
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                ("T55J5", HandType::FourOfAKind, Some('5'), 1, 1),
+                ("QQQJA", HandType::FourOfAKind, Some('Q'), 2, 600),
+                ("KTJJT", HandType::FourOfAKind, Some('T'), 3, 60),
+            ]
+        );
+
+        let json = explain("JJJJJ 7", &ExplanationFormat::Json).expect("explaining as json");
+        assert_eq!(
+            json,
+            "{\"a\":[{\"rank\":1,\"hand\":\"JJJJJ\",\"hand_type\":\"FiveOfAKind\",\"substitution\":null,\"bet\":7,\"winnings\":7}],\
+\"b\":[{\"rank\":1,\"hand\":\"JJJJJ\",\"hand_type\":\"FiveOfAKind\",\"substitution\":\"A\",\"bet\":7,\"winnings\":7}]}\n"
+        );
+
+        let table = explain("JJJJJ 7", &ExplanationFormat::Table).expect("explaining as table");
+        assert_eq!(table.lines().count(), 7);
+    }
+
+    /// Hands from a few labels, so that groups of equal cards and jokers are common.
+    fn hand() -> impl Strategy<Value = String> {
+        let label = prop::sample::select(vec!['2', '7', 'T', 'J', 'Q', 'A']);
+
+        prop::collection::vec(label, 5).prop_map(|labels| labels.into_iter().collect())
+    }
+
+    /// A hand, and either another hand or the same cards dealt in another order.
+    fn hand_pair() -> impl Strategy<Value = (String, String)> {
+        hand().prop_flat_map(|a| {
+            let reordered = Just(a.chars().collect::<Vec<char>>())
+                .prop_shuffle()
+                .prop_map(|labels| labels.into_iter().collect());
+
+            (Just(a), prop_oneof![hand(), reordered])
+        })
+    }
+
+    fn cards(rules: &Rules, hand: &str) -> Vec<Card> {
+        hand.chars()
+            .map(|label| rules.card(label).expect("known card"))
+            .collect()
+    }
+
+    proptest! {
+        #[test]
+        fn ranking_is_a_total_order((a, b) in hand_pair(), c in hand()) {
+            for rules in [Rules::camel_cards(), Rules::camel_cards_with_jokers()] {
+                let (rank_a, rank_b, rank_c) = (
+                    rules.rank(&cards(&rules, &a)),
+                    rules.rank(&cards(&rules, &b)),
+                    rules.rank(&cards(&rules, &c)),
+                );
+
+                // Only equal hands tie, so the winnings never depend on the order of the plays.
+                prop_assert_eq!(rank_a == rank_b, a == b);
+                prop_assert_eq!(rank_a.cmp(&rank_b), rank_b.cmp(&rank_a).reverse());
+                if rank_a <= rank_b && rank_b <= rank_c {
+                    prop_assert!(rank_a <= rank_c);
+                }
+            }
+        }
+
+        #[test]
+        fn jokers_never_weaken_a_hand(hand in hand()) {
+            let rules = Rules::camel_cards();
+            let jokers = Rules::camel_cards_with_jokers();
+
+            let hand_type =
+                HandType::from_count_signature(&rules.count_signature(&cards(&rules, &hand)));
+            let joker_hand_type =
+                HandType::from_count_signature(&jokers.count_signature(&cards(&jokers, &hand)));
+            prop_assert!(joker_hand_type >= hand_type);
+        }
+
+        #[test]
+        fn winnings_do_not_depend_on_play_order(
+            (plays, shuffled) in prop::collection::btree_map(hand(), 1..1000_u32, 0..10)
+                .prop_flat_map(|plays| {
+                    let plays: Vec<(String, u32)> = plays.into_iter().collect();
+                    (Just(plays.clone()), Just(plays).prop_shuffle())
+                })
+        ) {
+            // Equal hands with different bets would tie, so every hand is dealt once.
+            let data = |plays: &[(String, u32)]| {
+                plays
+                    .iter()
+                    .map(|(hand, bet)| format!("{} {}", hand, bet))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            };
+
+            prop_assert_eq!(
+                puzzle_a(&data(&plays)).expect("solving puzzle a"),
+                puzzle_a(&data(&shuffled)).expect("solving puzzle a")
+            );
+            prop_assert_eq!(
+                puzzle_b(&data(&plays)).expect("solving puzzle b"),
+                puzzle_b(&data(&shuffled)).expect("solving puzzle b")
             );
         }
     }
@@ -1,97 +1,234 @@
-use std::collections::HashMap;
 use std::fmt;
-use std::time::Instant;
 
-pub fn solution(data: &str) -> Result<(u32, u32), Box<dyn std::error::Error>> {
-    let sum_a = puzzle_a(&data)?;
-    let sum_b = puzzle_b(&data)?;
+use crate::cycle;
+use crate::graph::{self, Graph};
+use crate::math;
+use crate::progress::Progress;
 
-    println!("day 8 b: {}", sum_b);
+pub fn solution(
+    data: &str,
+    progress: &mut dyn Progress,
+) -> Result<(i128, i128), Box<dyn std::error::Error>> {
+    let sum_a = puzzle_a(data)?;
+    let sum_b = puzzle_b(data, progress)?;
 
-    Ok((sum_a, sum_b as u32))
+    Ok((sum_a.into(), sum_b.into()))
 }
 
-fn puzzle_a(data: &str) -> Result<u32, Box<dyn std::error::Error>> {
-    let node_start = must_parse_node_id("AAA");
-    let node_needle = must_parse_node_id("ZZZ");
-    let mut map = extract_map(data);
+pub fn puzzle_a(data: &str) -> Result<u64, Box<dyn std::error::Error>> {
+    let map = extract_map(data)?;
+    let node_start = map.node("AAA")?;
+    let node_needle = map.node("ZZZ")?;
 
-    Ok(map.traverse_graph(&node_start, &node_needle) as u32)
+    Ok(map.traverse_graph(node_start, node_needle)?)
 }
 
-fn puzzle_b(data: &str) -> Result<u64, Box<dyn std::error::Error>> {
-    let mut map = extract_map(data);
+pub fn puzzle_b(
+    data: &str,
+    progress: &mut dyn Progress,
+) -> Result<u64, Box<dyn std::error::Error>> {
+    let map = extract_map(data)?;
 
     let nodes_start = extract_starting_nodes(&map.nodes);
 
-    Ok(map.traverse_graph_simultaneous(nodes_start) as u64)
+    let ghost_cycles: Vec<GhostCycle> = nodes_start
+        .iter()
+        .map(|node_start| {
+            let ghost_cycle = map.find_cycle(*node_start);
+            progress.report(&format!(
+                "day 8: ghost from {} cycles every {} steps after {} steps",
+                map.nodes.name(*node_start),
+                ghost_cycle.period,
+                ghost_cycle.offset
+            ));
+
+            ghost_cycle
+        })
+        .collect();
+
+    Ok(steps_until_all_on_z(&ghost_cycles)?)
 }
 
-struct Map {
+pub struct Map {
     directions: DirectionList,
-    nodes: DirectedGraph,
+    // Every node has an edge for turning left and one for turning right, checked when parsing.
+    nodes: Graph<NodeName, Direction>,
 }
 
 impl Map {
-    fn traverse_graph(&mut self, node_start: &NodeId, node_needle: &NodeId) -> usize {
-        let mut cursor = node_start;
-        self.directions
-            .position(|direction| {
-                cursor = traverse(&self.nodes, &cursor, &direction);
-                if cursor == node_needle {
-                    true
+    fn node(&self, name: &str) -> Result<graph::NodeId, String> {
+        self.nodes
+            .id(&parse_node_name(name)?)
+            .ok_or_else(|| format!("node {} not found", name))
+    }
+
+    /// The amount of steps from the starting node to the first time the needle is reached. Every
+    /// state the walk can be in is seen once it has been through its cycle, so a needle that is
+    /// not reached by then never is.
+    fn traverse_graph(
+        &self,
+        node_start: graph::NodeId,
+        node_needle: graph::NodeId,
+    ) -> Result<u64, String> {
+        let mut steps: u64 = 0;
+        let mut needle_step = None;
+        let _ = cycle::find_cycle_hashed((node_start, 0), |state| {
+            let next = self.step(state);
+            steps += 1;
+            if next.0 == node_needle && needle_step.is_none() {
+                needle_step = Some(steps);
+            }
+
+            next
+        });
+
+        needle_step.ok_or_else(|| {
+            format!(
+                "node {} cannot be reached from node {}",
+                self.nodes.name(node_needle),
+                self.nodes.name(node_start)
+            )
+        })
+    }
+
+    /// Follows the direction at the cursor, and moves the cursor on to the next direction. The
+    /// state is the node together with the position in the direction list, since the same node
+    /// can be followed by different directions.
+    fn step(&self, &(node, cursor): &(graph::NodeId, usize)) -> (graph::NodeId, usize) {
+        let directions = &self.directions.directions;
+
+        (
+            traverse(&self.nodes, node, &directions[cursor]),
+            (cursor + 1) % directions.len(),
+        )
+    }
+
+    /// Walks from the starting node until the ghost is back in a state it has been in before.
+    fn find_cycle(&self, node_start: graph::NodeId) -> GhostCycle {
+        let transition = |state: &(graph::NodeId, usize)| self.step(state);
+
+        let cycle = cycle::find_cycle_hashed((node_start, 0), transition);
+
+        let mut z_steps_before_cycle = vec![];
+        let mut z_steps_in_cycle = vec![];
+        let mut state = (node_start, 0);
+        for step in 0..(cycle.prefix + cycle.period) {
+            if self.nodes.name(state.0).is_end() {
+                if step < cycle.prefix {
+                    z_steps_before_cycle.push(step);
                 } else {
-                    false
+                    z_steps_in_cycle.push(step);
                 }
-            })
-            .expect("could not find the desired node")
-            + 1 // The desired position is 1-indexed.
+            }
+            state = transition(&state);
+        }
+
+        GhostCycle {
+            offset: cycle.prefix,
+            period: cycle.period,
+            z_steps_before_cycle,
+            z_steps_in_cycle,
+        }
     }
+}
 
-    fn traverse_graph_simultaneous(&mut self, nodes_start: Vec<NodeId>) -> usize {
-        let now = Instant::now();
-        let mut counter: u64 = 0;
-        let base: u64 = 2;
-        let mut power_of_two: u32 = 0;
-        let mut cursors = nodes_start.clone();
-
-        println!("Nodes at {}: {}", counter, format_nodes(&nodes_start));
-
-        self.directions
-            .position(|direction| {
-                cursors = cursors
-                    .iter()
-                    .map(|node| (*traverse(&self.nodes, node, &direction)).clone())
-                    .collect();
-
-                counter += 1;
-                if counter >= base.pow(power_of_two) {
-                    println!(
-                        "Index {} after {}s: {}",
-                        counter,
-                        now.elapsed().as_secs(),
-                        format_nodes(&cursors)
-                    );
-                    power_of_two += 1;
-                }
+/// The steps at which a ghost stands on a node ending in `Z`. After `offset` steps the ghost
+/// repeats the same `period` steps forever.
+struct GhostCycle {
+    offset: u64,
+    period: u64,
+    z_steps_before_cycle: Vec<u64>,
+    // The steps in the first pass through the cycle, from `offset` up to `offset + period`.
+    z_steps_in_cycle: Vec<u64>,
+}
 
-                cursors.iter().all(|node| node.0[2] == 'Z')
-            })
-            .expect("could not find the desired node")
-            + 1 // The desired position is 1-indexed.
+impl GhostCycle {
+    fn is_on_z(&self, step: u64) -> bool {
+        if step < self.offset {
+            return self.z_steps_before_cycle.contains(&step);
+        }
+
+        let step_in_cycle = self.offset + (step - self.offset) % self.period;
+        self.z_steps_in_cycle.contains(&step_in_cycle)
+    }
+
+    // In the puzzle input, every ghost reaches its only `Z` node exactly at the end of each cycle.
+    // All ghosts are then on a `Z` node after any common multiple of their periods.
+    fn is_simple(&self) -> bool {
+        self.z_steps_before_cycle.is_empty()
+            && self.z_steps_in_cycle.len() == 1
+            && self.z_steps_in_cycle[0] == self.period
     }
 }
 
-fn format_nodes(nodes: &[NodeId]) -> String {
-    let formatted_ids = nodes
+fn steps_until_all_on_z(ghost_cycles: &[GhostCycle]) -> Result<u64, String> {
+    if ghost_cycles.is_empty() {
+        return Err("no starting nodes ending in A".to_string());
+    }
+
+    if ghost_cycles.iter().all(GhostCycle::is_simple) {
+        let periods: Vec<u64> = ghost_cycles
+            .iter()
+            .map(|ghost_cycle| ghost_cycle.period)
+            .collect();
+
+        return math::lcm_all(&periods).ok_or("overflow: steps do not fit in u64".to_string());
+    }
+
+    // Before every ghost has entered its cycle, the ghosts can only meet on `Z` nodes at a step
+    // before the cycle of the ghost that enters it last.
+    let all_in_cycle = ghost_cycles
         .iter()
-        .map(|id| id.to_string())
-        .collect::<Vec<String>>()
-        .join(", ");
-    formatted_ids
+        .map(|ghost_cycle| ghost_cycle.offset)
+        .max()
+        .unwrap_or(0);
+
+    let early_steps = ghost_cycles
+        .iter()
+        .flat_map(|ghost_cycle| ghost_cycle.z_steps_before_cycle.iter())
+        .filter(|&&step| step < all_in_cycle)
+        .filter(|&&step| {
+            ghost_cycles
+                .iter()
+                .all(|ghost_cycle| ghost_cycle.is_on_z(step))
+        })
+        .min();
+
+    if let Some(&step) = early_steps {
+        return Ok(step);
+    }
+
+    // Afterwards, every combination of one `Z` step per ghost gives a system of congruences.
+    let mut congruences: Vec<(u64, u64)> = vec![(0, 1)];
+    for ghost_cycle in ghost_cycles {
+        let mut combined = vec![];
+        for &(remainder, modulus) in congruences.iter() {
+            for &z_step in ghost_cycle.z_steps_in_cycle.iter() {
+                let congruence = (z_step % ghost_cycle.period, ghost_cycle.period);
+                if let Some(solution) = math::crt(&[(remainder, modulus), congruence])? {
+                    combined.push(solution);
+                }
+            }
+        }
+        combined.sort();
+        combined.dedup();
+        congruences = combined;
+    }
+
+    congruences
+        .into_iter()
+        .map(|(remainder, modulus)| {
+            if remainder >= all_in_cycle {
+                remainder
+            } else {
+                remainder + (all_in_cycle - remainder).div_ceil(modulus) * modulus
+            }
+        })
+        .min()
+        .ok_or("the ghosts are never on nodes ending in Z at the same time".to_string())
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 enum Direction {
     Left,
     Right,
@@ -99,207 +236,241 @@ enum Direction {
 
 struct DirectionList {
     directions: Vec<Direction>,
-    cursor: usize,
 }
 
 impl DirectionList {
     fn new(directions: Vec<Direction>) -> Self {
-        DirectionList {
-            directions,
-            cursor: 0,
-        }
+        DirectionList { directions }
     }
 }
 
-impl Iterator for DirectionList {
-    type Item = Direction;
-
-    fn next(&mut self) -> Option<Direction> {
-        let direction = self.directions[self.cursor].clone();
-        self.cursor = (self.cursor + 1) % self.directions.len();
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+struct NodeName([char; 3]);
 
-        Some(direction)
+impl NodeName {
+    fn is_end(&self) -> bool {
+        self.0[2] == 'Z'
     }
 }
 
-#[derive(PartialEq, Eq, Hash, Clone, Debug)]
-struct NodeId([char; 3]);
-
-impl fmt::Display for NodeId {
+impl fmt::Display for NodeName {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}{}{}", self.0[0], self.0[1], self.0[2])
     }
 }
 
-impl From<Vec<char>> for NodeId {
-    fn from(chars: Vec<char>) -> NodeId {
-        NodeId(
-            chars
-                .into_iter()
-                .take(3)
-                .collect::<Vec<_>>()
-                .try_into()
-                .expect("could not convert chars to node id"),
-        )
-    }
-}
-
-type DirectedGraph = HashMap<NodeId, NodeDirectory>;
-
-struct NodeDirectory {
-    left: NodeId,
-    right: NodeId,
-}
-
-fn traverse<'a>(
-    graph: &'a DirectedGraph,
-    node_start: &NodeId,
+/// Follows the edge for the given direction.
+fn traverse(
+    graph: &Graph<NodeName, Direction>,
+    node: graph::NodeId,
     direction: &Direction,
-) -> &'a NodeId {
-    let node = graph.get(node_start).expect("node id not found");
-    match direction {
-        Direction::Left => &node.left,
-        Direction::Right => &node.right,
-    }
+) -> graph::NodeId {
+    graph
+        .outgoing(node)
+        .find(|edge| edge.weight == *direction)
+        .map(|edge| edge.to)
+        .expect("every node has an edge in both directions")
 }
 
-fn extract_starting_nodes(graph: &DirectedGraph) -> Vec<NodeId> {
+fn extract_starting_nodes(graph: &Graph<NodeName, Direction>) -> Vec<graph::NodeId> {
     graph
-        .iter()
-        .filter_map(|(node, _)| match (*node).0[2] == 'A' {
-            true => Some((*node).clone()),
-            false => None,
-        })
+        .nodes()
+        .filter(|&node| graph.name(node).0[2] == 'A')
         .collect()
 }
 
-fn extract_map(data: &str) -> Map {
-    let directions = extract_directions(data.lines().next().expect("could not find first line"));
-    let graph = extract_graph(data);
-    Map {
-        directions,
-        nodes: graph,
+/// Parses the direction list on the first line and the nodes below it, such as
+/// `AAA = (BBB, CCC)`. Every node that is turned to has to be listed once.
+pub fn extract_map(data: &str) -> Result<Map, Box<dyn std::error::Error>> {
+    let mut lines = data.lines();
+    let directions = extract_directions(lines.next().ok_or("parse error: data is empty")?)?;
+    let nodes = extract_graph(lines)?;
+
+    if let Some(node) = nodes
+        .nodes()
+        .find(|&node| nodes.outgoing(node).next().is_none())
+    {
+        return Err(format!("parse error: node {} is never listed", nodes.name(node)).into());
     }
+
+    Ok(Map { directions, nodes })
 }
 
-fn extract_directions(directions_str: &str) -> DirectionList {
+fn extract_directions(directions_str: &str) -> Result<DirectionList, String> {
     let directions = directions_str
         .trim()
         .chars()
         .map(|direction_character| match direction_character {
-            'L' => Direction::Left,
-            'R' => Direction::Right,
-            _ => panic!("encountered unexpected non 'L/R' direction character"),
+            'L' => Ok(Direction::Left),
+            'R' => Ok(Direction::Right),
+            _ => Err(format!(
+                "parse error: direction {:?} is not 'L' or 'R'",
+                direction_character
+            )),
         })
-        .collect();
+        .collect::<Result<Vec<Direction>, String>>()?;
 
-    DirectionList::new(directions)
-}
-
-fn extract_graph(data: &str) -> DirectedGraph {
-    let mut graph: DirectedGraph = DirectedGraph::new();
-
-    data.lines().skip(2).for_each(|line| {
-        let mut line_iter = line.split_whitespace();
-        let node_id = must_parse_node_id(line_iter.next().expect("no first node found"));
-
-        let mut line_iter = line_iter.skip(1);
+    if directions.is_empty() {
+        return Err("parse error: no directions found".to_string());
+    }
 
-        let left_node_id_str = line_iter.next().expect("no left node found");
-        let left_node_id = must_parse_node_id(&left_node_id_str[1..=3]);
+    Ok(DirectionList::new(directions))
+}
 
-        let right_node_id_str = line_iter.next().expect("no right node found");
-        let right_node_id = must_parse_node_id(&right_node_id_str[..=2]);
+fn extract_graph<'a>(
+    lines: impl Iterator<Item = &'a str>,
+) -> Result<Graph<NodeName, Direction>, String> {
+    let mut graph = Graph::new();
+
+    for line in lines.filter(|line| !line.trim().is_empty()) {
+        let (node_str, neighbours_str) = line
+            .split_once('=')
+            .ok_or_else(|| format!("parse error: no '=' in node {:?}", line))?;
+        let (left_node_str, right_node_str) = neighbours_str
+            .trim()
+            .strip_prefix('(')
+            .and_then(|neighbours| neighbours.strip_suffix(')'))
+            .and_then(|neighbours| neighbours.split_once(','))
+            .ok_or_else(|| format!("parse error: no (left, right) nodes in {:?}", line))?;
+
+        let node = graph.intern(parse_node_name(node_str)?);
+        if graph.outgoing(node).next().is_some() {
+            return Err(format!(
+                "parse error: node {} is listed twice",
+                graph.name(node)
+            ));
+        }
+        let left_node = graph.intern(parse_node_name(left_node_str)?);
+        let right_node = graph.intern(parse_node_name(right_node_str)?);
 
-        let _ = graph.insert(
-            node_id,
-            NodeDirectory {
-                left: left_node_id,
-                right: right_node_id,
-            },
-        );
-    });
+        graph.add_edge(node, left_node, Direction::Left);
+        graph.add_edge(node, right_node, Direction::Right);
+    }
 
-    graph
+    Ok(graph)
 }
 
-fn must_parse_node_id(node_id_string: &str) -> NodeId {
-    node_id_string
-        .chars()
-        .take(3)
-        .collect::<Vec<char>>()
-        .try_into()
-        .expect("could not convert data to node id")
+/// Parses a node name of exactly three characters, ignoring surrounding whitespace.
+fn parse_node_name(node_name_string: &str) -> Result<NodeName, String> {
+    let chars: Vec<char> = node_name_string.trim().chars().collect();
+
+    chars.try_into().map(NodeName).map_err(|_| {
+        format!(
+            "parse error: node name {:?} is not three characters",
+            node_name_string
+        )
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    struct TestCase {
-        input: String,
-        expected_output_a: u32,
-        expected_output_b: u64,
-    }
+    use crate::progress::Silent;
 
     #[test]
-    fn puzzle() {
+    fn ghost_cycles() {
+        struct GhostTestCase {
+            input: &'static str,
+            expected_steps: Option<u64>,
+        }
+
         let test_cases = vec![
-            TestCase {
-                input: "RL
-
-AAA = (BBB, CCC)
-BBB = (DDD, EEE)
-CCC = (ZZZ, GGG)
-DDD = (DDD, DDD)
-EEE = (EEE, EEE)
-GGG = (GGG, GGG)
-ZZZ = (ZZZ, ZZZ)"
-                    .into(),
-                expected_output_a: 2,
-                expected_output_b: 2,
+            // The ghosts are on a `Z` node at steps 1 (mod 3) and 2 (mod 4), but not at a common
+            // multiple of their periods.
+            GhostTestCase {
+                input: "L
+
+AAA = (AAZ, XXX)
+AAZ = (AAB, XXX)
+AAB = (AAA, XXX)
+BBA = (BBB, XXX)
+BBB = (BBZ, XXX)
+BBZ = (BBC, XXX)
+BBC = (BBA, XXX)
+XXX = (XXX, XXX)",
+                expected_steps: Some(10),
+            },
+            // The second ghost only passes a `Z` node before it enters its cycle.
+            GhostTestCase {
+                input: "L
+
+AAA = (AAZ, XXX)
+AAZ = (AAB, XXX)
+AAB = (AAA, XXX)
+CCA = (CCZ, XXX)
+CCZ = (CCB, XXX)
+CCB = (CCB, XXX)
+XXX = (XXX, XXX)",
+                expected_steps: Some(1),
             },
-            TestCase {
-                input: "LLR
-
-AAA = (BBB, BBB)
-BBB = (AAA, ZZZ)
-ZZZ = (ZZZ, ZZZ)"
-                    .into(),
-                expected_output_a: 6,
-                expected_output_b: 6,
+            // The ghosts are on a `Z` node at odd and even steps only.
+            GhostTestCase {
+                input: "L
+
+AAA = (AAZ, XXX)
+AAZ = (AAA, XXX)
+BBA = (BBB, XXX)
+BBB = (BBZ, XXX)
+BBZ = (BBC, XXX)
+BBC = (BBA, XXX)
+XXX = (XXX, XXX)",
+                expected_steps: None,
             },
-            TestCase {
-                input: "LR
-
-AAA = (11B, XXX)
-11B = (XXX, ZZZ)
-ZZZ = (11B, XXX)
-22A = (22B, XXX)
-22B = (22C, 22C)
-22C = (22Z, 22Z)
-22Z = (22B, 22B)
-XXX = (XXX, XXX)"
-                    .into(),
-                expected_output_a: 2,
-                expected_output_b: 6,
+            // No node ends in `A`, so there are no ghosts to wait for.
+            GhostTestCase {
+                input: "L
+
+BBB = (BBB, BBB)",
+                expected_steps: None,
             },
         ];
 
         for test_case in test_cases {
-            let output = puzzle_a(&test_case.input).expect("solving puzzle a");
+            let output = puzzle_b(test_case.input, &mut Silent).ok();
             assert_eq!(
-                output, test_case.expected_output_a,
+                output, test_case.expected_steps,
                 "input: {}",
                 test_case.input,
             );
+        }
+    }
 
-            let output = puzzle_b(&test_case.input).expect("solving puzzle b");
-            assert_eq!(
-                output, test_case.expected_output_b,
-                "input: {}",
-                test_case.input,
-            );
+    #[test]
+    fn malformed_maps() {
+        let test_cases = vec![
+            "",
+            "LXR\n\nAAA = (AAA, AAA)",
+            "\n\nAAA = (AAA, AAA)",
+            "L\n\nAAA = (BBB, AAA)",
+            "L\n\nAAA = (AAA, AAA)\nAAA = (AAA, AAA)",
+            "L\n\nAAA (AAA, AAA)",
+            "L\n\nAAA = (AAA AAA)",
+            "L\n\nAA = (AA, AA)",
+            "L\n\nAAA = (€, AAA)",
+            "L\n\nAAA = (AAAA, AAA)",
+        ];
+
+        for input in test_cases {
+            assert!(extract_map(input).is_err(), "input: {:?}", input);
+            assert!(puzzle_a(input).is_err(), "input: {:?}", input);
         }
+
+        // A map without nodes is well formed, but has no node to start from.
+        assert!(extract_map("LR").is_ok());
+        assert!(puzzle_a("LR").is_err());
+
+        let map = extract_map("RL\n\nÅÅÅ = (ZZZ, ÅÅÅ)\nZZZ = (ZZZ, ZZZ)").expect("parsing map");
+        assert!(map.node("ÅÅÅ").is_ok());
+        assert!(map.node("ÅÅ").is_err());
+    }
+
+    #[test]
+    fn unreachable_needle() {
+        assert!(puzzle_a("L\n\nAAA = (AAA, AAA)\nZZZ = (ZZZ, ZZZ)").is_err());
+        assert!(puzzle_a("LR\n\nAAA = (BBB, AAA)\nBBB = (AAA, BBB)\nZZZ = (ZZZ, ZZZ)").is_err());
+
+        // ZZZ is only reached by turning right from BBB, at the end of the directions.
+        let input = "LLLR\n\nAAA = (BBB, AAA)\nBBB = (AAA, ZZZ)\nZZZ = (ZZZ, ZZZ)";
+        assert_eq!(puzzle_a(input).expect("solving puzzle a"), 4);
     }
 }
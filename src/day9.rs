@@ -1,120 +1,269 @@
-pub fn solution(data: &str) -> Result<(u32, u32), Box<dyn std::error::Error>> {
-    let sum_a = puzzle_a(&data)?;
-    let sum_b = puzzle_b(&data)?;
+use crate::progress::Progress;
 
-    Ok((sum_a as u32, sum_b as u32))
+pub fn solution(
+    data: &str,
+    _progress: &mut dyn Progress,
+) -> Result<(i128, i128), Box<dyn std::error::Error>> {
+    let sum_a = puzzle_a(data)?;
+    let sum_b = puzzle_b(data)?;
+
+    Ok((sum_a.into(), sum_b.into()))
 }
 
-fn puzzle_a(data: &str) -> Result<i32, Box<dyn std::error::Error>> {
-    let history_rows = extract_history_rows(data);
+pub fn puzzle_a(data: &str) -> Result<i64, Box<dyn std::error::Error>> {
+    let history_rows = extract_history_rows(data)?;
 
-    let sum = history_rows.iter().map(|row| predict_next_value(row)).sum();
+    let mut sum: i64 = 0;
+    for row in history_rows.iter() {
+        let next_value = extrapolate(row, row.len() as i64)?;
+        sum = sum
+            .checked_add(next_value)
+            .ok_or("overflow: sum of next values")?;
+    }
 
     Ok(sum)
 }
 
-fn puzzle_b(data: &str) -> Result<i32, Box<dyn std::error::Error>> {
-    let history_rows = extract_history_rows(data);
+pub fn puzzle_b(data: &str) -> Result<i64, Box<dyn std::error::Error>> {
+    let history_rows = extract_history_rows(data)?;
 
-    let sum = history_rows
-        .iter()
-        .map(|row| predict_previous_value(row))
-        .sum();
+    let mut sum: i64 = 0;
+    for row in history_rows.iter() {
+        let previous_value = extrapolate(row, -1)?;
+        sum = sum
+            .checked_add(previous_value)
+            .ok_or("overflow: sum of previous values")?;
+    }
 
     Ok(sum)
 }
 
-fn extract_history_rows(data: &str) -> Vec<Vec<i32>> {
+/// Parses one history of numbers per line, skipping blank lines.
+pub fn extract_history_rows(data: &str) -> Result<Vec<Vec<i64>>, std::num::ParseIntError> {
     data.lines()
+        .filter(|row| !row.trim().is_empty())
         .map(|row| {
             row.split_whitespace()
-                .map(|number_str| {
-                    number_str
-                        .parse::<i32>()
-                        .expect("found a string that was not a number")
-                })
+                .map(|number_str| number_str.parse::<i64>())
                 .collect()
         })
         .collect()
 }
 
-fn predict_previous_value(row: &[i32]) -> i32 {
-    let first_value = row.iter().nth(0).expect("row must have a first value");
+/// Predicts the value at any index of a history, where index 0 is its first value. Indices past
+/// the end predict the future, negative indices the past.
+pub fn extrapolate(history: &[i64], index: i64) -> Result<i64, String> {
+    NewtonPolynomial::fit(history)?.evaluate(index)
+}
 
-    match row.iter().all(|number| *number == 0) {
-        true => 0,
-        false => {
-            let next_row: Vec<i32> = vec![0]
-                .iter()
-                .chain(row.iter())
-                .zip(row.iter())
-                .map(|(first, next)| next - first)
-                .collect();
+/// The polynomial of lowest degree through all values of a history, in Newton's forward
+/// difference form: `value(x) = sum over k of differences[k] * binomial(x, k)`, where
+/// `differences[k]` is the first value of the k-th row of differences.
+pub struct NewtonPolynomial {
+    differences: Vec<i64>,
+}
 
-            first_value - predict_previous_value(&next_row[1..])
+impl NewtonPolynomial {
+    pub fn fit(history: &[i64]) -> Result<NewtonPolynomial, String> {
+        if history.is_empty() {
+            return Err("cannot fit a polynomial through an empty history".to_string());
         }
+
+        let mut differences = vec![];
+        let mut row = history.to_vec();
+
+        while row.iter().any(|&value| value != 0) {
+            differences.push(row[0]);
+            row = row
+                .windows(2)
+                .map(|pair| pair[1].checked_sub(pair[0]))
+                .collect::<Option<Vec<i64>>>()
+                .ok_or("overflow: difference between values does not fit in i64")?;
+        }
+
+        Ok(NewtonPolynomial { differences })
     }
-}
-fn predict_next_value(row: &[i32]) -> i32 {
-    let last_value = row.iter().nth_back(0).expect("row must have a last value");
 
-    match row.iter().all(|number| *number == 0) {
-        true => 0,
-        false => {
-            let next_row: Vec<i32> = vec![0]
-                .iter()
-                .chain(row.iter())
-                .zip(row.iter())
-                .map(|(first, next)| next - first)
-                .collect();
+    pub fn evaluate(&self, index: i64) -> Result<i64, String> {
+        let overflow = || format!("overflow: value at index {} does not fit in i64", index);
+        let index = index as i128;
 
-            last_value + predict_next_value(&next_row[1..])
+        let mut value: i128 = 0;
+        let mut binomial: i128 = 1;
+
+        for (k, &difference) in self.differences.iter().enumerate() {
+            if k > 0 {
+                // binomial(x, k) = binomial(x, k - 1) * (x - k + 1) / k, which divides exactly.
+                let k = k as i128;
+                binomial = binomial.checked_mul(index - k + 1).ok_or_else(overflow)? / k;
+            }
+
+            let term = binomial
+                .checked_mul(difference as i128)
+                .ok_or_else(overflow)?;
+            value = value.checked_add(term).ok_or_else(overflow)?;
         }
+
+        i64::try_from(value).map_err(|_| overflow())
+    }
+
+    /// The coefficients of the polynomial in the usual form, from the constant term upwards. They
+    /// can be fractions, so they are returned as integer numerators over one common denominator.
+    pub fn coefficients(&self) -> Result<(Vec<i128>, i128), String> {
+        let overflow = || "overflow: coefficients do not fit in i128".to_string();
+
+        let degree = self.differences.len().saturating_sub(1);
+        let denominator: i128 = (1..=degree as i128)
+            .try_fold(1_i128, |factorial, k| factorial.checked_mul(k))
+            .ok_or_else(overflow)?;
+
+        let mut numerators = vec![0_i128; degree + 1];
+        // The coefficients of x * (x - 1) * ... * (x - k + 1), which is k! * binomial(x, k).
+        let mut falling_factorial = vec![1_i128];
+        let mut k_factorial: i128 = 1;
+
+        for (k, &difference) in self.differences.iter().enumerate() {
+            if k > 0 {
+                let mut next = vec![0_i128; falling_factorial.len() + 1];
+                for (power, &coefficient) in falling_factorial.iter().enumerate() {
+                    next[power + 1] = next[power + 1]
+                        .checked_add(coefficient)
+                        .ok_or_else(overflow)?;
+                    next[power] = coefficient
+                        .checked_mul(-(k as i128 - 1))
+                        .and_then(|product| next[power].checked_add(product))
+                        .ok_or_else(overflow)?;
+                }
+                falling_factorial = next;
+                k_factorial *= k as i128;
+            }
+
+            // difference * binomial(x, k) * degree! = difference * (degree! / k!) * falling
+            let scale = (denominator / k_factorial)
+                .checked_mul(difference as i128)
+                .ok_or_else(overflow)?;
+            for (power, &coefficient) in falling_factorial.iter().enumerate() {
+                numerators[power] = coefficient
+                    .checked_mul(scale)
+                    .and_then(|product| numerators[power].checked_add(product))
+                    .ok_or_else(overflow)?;
+            }
+        }
+
+        Ok((numerators, denominator))
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use super::*;
 
-    struct TestCase {
-        input: String,
-        expected_output_a: i32,
-        expected_output_b: i32,
+    #[test]
+    fn extrapolation() {
+        let squares = [0, 1, 4, 9];
+        assert_eq!(extrapolate(&squares, 10), Ok(100));
+        assert_eq!(extrapolate(&squares, -3), Ok(9));
+
+        let history = [10, 13, 16, 21, 30, 45];
+        assert_eq!(extrapolate(&history, 6), Ok(68));
+        assert_eq!(extrapolate(&history, -1), Ok(5));
+        assert_eq!(extrapolate(&history, 2), Ok(16));
+
+        let constant = [-7, -7, -7];
+        assert_eq!(extrapolate(&constant, 1_000_000), Ok(-7));
+
+        assert_eq!(extrapolate(&[0, 0], 5), Ok(0));
+        assert!(extrapolate(&[], 0).is_err());
+        assert!(extrapolate(&[i64::MIN, i64::MAX], 2).is_err());
+        assert!(extrapolate(&[0, i64::MAX], 2).is_err());
+        assert_eq!(extrapolate(&[0, i64::MAX], 1), Ok(i64::MAX));
     }
 
     #[test]
-    fn puzzle() {
-        let test_cases = vec![
-            TestCase {
-                input: "0 3 6 9 12 15".into(),
-                expected_output_a: 18,
-                expected_output_b: -3,
-            },
-            TestCase {
-                input: "0 3 6 9 12 15
-1 3 6 10 15 21
-10 13 16 21 30 45"
-                    .into(),
-                expected_output_a: 114,
-                expected_output_b: 2,
+    fn coefficients() {
+        // x^2 / 2 + x / 2 + 1
+        let polynomial = NewtonPolynomial::fit(&[1, 2, 4, 7]).expect("fitting polynomial");
+        assert_eq!(polynomial.coefficients(), Ok((vec![2, 1, 1], 2)));
+
+        // 2x^3 - 3x + 5
+        let history: Vec<i64> = (0..6).map(|x| 2 * x * x * x - 3 * x + 5).collect();
+        let polynomial = NewtonPolynomial::fit(&history).expect("fitting polynomial");
+        assert_eq!(polynomial.coefficients(), Ok((vec![30, -18, 0, 12], 6)));
+
+        let polynomial = NewtonPolynomial::fit(&[0, 0, 0]).expect("fitting polynomial");
+        assert_eq!(polynomial.coefficients(), Ok((vec![0], 1)));
+    }
+
+    /// The coefficients of a polynomial from the constant term up, with a leading coefficient
+    /// that is not 0.
+    fn polynomial() -> impl Strategy<Value = Vec<i64>> {
+        let leading = prop_oneof![-9..0_i64, 1..10_i64];
+
+        (prop::collection::vec(-20..20_i64, 0..5), leading).prop_map(
+            |(mut coefficients, leading)| {
+                coefficients.push(leading);
+                coefficients
             },
-        ];
-
-        for test_case in test_cases {
-            let output = puzzle_a(&test_case.input).expect("solving puzzle a");
-            assert_eq!(
-                output, test_case.expected_output_a,
-                "input: {}",
-                test_case.input,
-            );
-
-            let output = puzzle_b(&test_case.input).expect("solving puzzle b");
-            assert_eq!(
-                output, test_case.expected_output_b,
-                "input: {}",
-                test_case.input,
-            );
+        )
+    }
+
+    fn value_at(coefficients: &[i64], x: i64) -> i64 {
+        coefficients
+            .iter()
+            .rev()
+            .fold(0, |value, coefficient| value * x + coefficient)
+    }
+
+    fn history(coefficients: &[i64], length: usize) -> Vec<i64> {
+        (0..length as i64)
+            .map(|x| value_at(coefficients, x))
+            .collect()
+    }
+
+    proptest! {
+        #[test]
+        fn extrapolation_of_a_polynomial_is_exact(
+            coefficients in polynomial(),
+            extra_values in 0..4_usize,
+            index in -30..30_i64,
+        ) {
+            let history = history(&coefficients, coefficients.len() + extra_values);
+
+            prop_assert_eq!(extrapolate(&history, index), Ok(value_at(&coefficients, index)));
+
+            // The fit has the degree of the polynomial, whatever the amount of values beyond it.
+            let (numerators, denominator) = NewtonPolynomial::fit(&history)
+                .and_then(|polynomial| polynomial.coefficients())
+                .expect("fitting polynomial");
+            let expected: Vec<i128> = coefficients
+                .iter()
+                .map(|&coefficient| coefficient as i128 * denominator)
+                .collect();
+            prop_assert_eq!(numerators, expected);
+        }
+
+        #[test]
+        fn puzzles_sum_the_extrapolations(polynomials in prop::collection::vec(polynomial(), 0..5)) {
+            let histories: Vec<Vec<i64>> = polynomials
+                .iter()
+                .map(|coefficients| history(coefficients, coefficients.len() + 1))
+                .collect();
+            let data = histories
+                .iter()
+                .map(|history| history.iter().map(i64::to_string).collect::<Vec<_>>().join(" "))
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            let next_values = polynomials
+                .iter()
+                .zip(&histories)
+                .map(|(coefficients, history)| value_at(coefficients, history.len() as i64));
+            let previous_values = polynomials.iter().map(|coefficients| value_at(coefficients, -1));
+
+            prop_assert_eq!(puzzle_a(&data).expect("solving puzzle a"), next_values.sum());
+            prop_assert_eq!(puzzle_b(&data).expect("solving puzzle b"), previous_values.sum());
         }
     }
 }
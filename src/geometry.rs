@@ -0,0 +1,737 @@
+use std::cmp::Ordering;
+use std::ops::{Add, Mul, Sub};
+
+use crate::math;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Point2 {
+    pub x: i64,
+    pub y: i64,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Vector2 {
+    pub x: i64,
+    pub y: i64,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Point3 {
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Vector3 {
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
+}
+
+impl Point2 {
+    pub fn new(x: i64, y: i64) -> Point2 {
+        Point2 { x, y }
+    }
+
+    /// The point moved by the vector, or `None` if it does not fit in an i64.
+    pub fn checked_add(&self, vector: &Vector2) -> Option<Point2> {
+        Some(Point2::new(
+            self.x.checked_add(vector.x)?,
+            self.y.checked_add(vector.y)?,
+        ))
+    }
+
+    /// The vector from `other` to this point, or `None` if it does not fit in an i64.
+    pub fn checked_sub(&self, other: &Point2) -> Option<Vector2> {
+        Some(Vector2::new(
+            self.x.checked_sub(other.x)?,
+            self.y.checked_sub(other.y)?,
+        ))
+    }
+}
+
+impl Vector2 {
+    pub fn new(x: i64, y: i64) -> Vector2 {
+        Vector2 { x, y }
+    }
+
+    /// The vector scaled by the factor, or `None` if it does not fit in an i64.
+    pub fn checked_mul(&self, factor: i64) -> Option<Vector2> {
+        Some(Vector2::new(
+            self.x.checked_mul(factor)?,
+            self.y.checked_mul(factor)?,
+        ))
+    }
+
+    /// The z component of the cross product, which is positive when `other` turns
+    /// counterclockwise from this vector.
+    pub fn cross(&self, other: &Vector2) -> i128 {
+        self.x as i128 * other.y as i128 - self.y as i128 * other.x as i128
+    }
+
+    pub fn dot(&self, other: &Vector2) -> i128 {
+        self.x as i128 * other.x as i128 + self.y as i128 * other.y as i128
+    }
+}
+
+impl Point3 {
+    pub fn new(x: i64, y: i64, z: i64) -> Point3 {
+        Point3 { x, y, z }
+    }
+
+    /// The point moved by the vector, or `None` if it does not fit in an i64.
+    pub fn checked_add(&self, vector: &Vector3) -> Option<Point3> {
+        Some(Point3::new(
+            self.x.checked_add(vector.x)?,
+            self.y.checked_add(vector.y)?,
+            self.z.checked_add(vector.z)?,
+        ))
+    }
+
+    /// The vector from `other` to this point, or `None` if it does not fit in an i64.
+    pub fn checked_sub(&self, other: &Point3) -> Option<Vector3> {
+        Some(Vector3::new(
+            self.x.checked_sub(other.x)?,
+            self.y.checked_sub(other.y)?,
+            self.z.checked_sub(other.z)?,
+        ))
+    }
+}
+
+impl Vector3 {
+    pub fn new(x: i64, y: i64, z: i64) -> Vector3 {
+        Vector3 { x, y, z }
+    }
+
+    /// The vector scaled by the factor, or `None` if it does not fit in an i64.
+    pub fn checked_mul(&self, factor: i64) -> Option<Vector3> {
+        Some(Vector3::new(
+            self.x.checked_mul(factor)?,
+            self.y.checked_mul(factor)?,
+            self.z.checked_mul(factor)?,
+        ))
+    }
+}
+
+// The operators panic on overflow, in release builds as well, rather than wrap around. Use the
+// checked methods where coordinates may not fit.
+
+impl Add<Vector2> for Point2 {
+    type Output = Point2;
+
+    fn add(self, vector: Vector2) -> Point2 {
+        self.checked_add(&vector)
+            .expect("overflow: point does not fit in i64")
+    }
+}
+
+impl Sub for Point2 {
+    type Output = Vector2;
+
+    fn sub(self, other: Point2) -> Vector2 {
+        self.checked_sub(&other)
+            .expect("overflow: vector does not fit in i64")
+    }
+}
+
+impl Mul<i64> for Vector2 {
+    type Output = Vector2;
+
+    fn mul(self, factor: i64) -> Vector2 {
+        self.checked_mul(factor)
+            .expect("overflow: vector does not fit in i64")
+    }
+}
+
+impl Add<Vector3> for Point3 {
+    type Output = Point3;
+
+    fn add(self, vector: Vector3) -> Point3 {
+        self.checked_add(&vector)
+            .expect("overflow: point does not fit in i64")
+    }
+}
+
+impl Sub for Point3 {
+    type Output = Vector3;
+
+    fn sub(self, other: Point3) -> Vector3 {
+        self.checked_sub(&other)
+            .expect("overflow: vector does not fit in i64")
+    }
+}
+
+impl Mul<i64> for Vector3 {
+    type Output = Vector3;
+
+    fn mul(self, factor: i64) -> Vector3 {
+        self.checked_mul(factor)
+            .expect("overflow: vector does not fit in i64")
+    }
+}
+
+/// Twice the signed area of the polygon through the vertices, by the shoelace formula. The area
+/// is positive when the vertices run counterclockwise. The last vertex connects back to the
+/// first. Returns `None` if the area does not fit in an i128.
+pub fn twice_signed_area(vertices: &[Point2]) -> Option<i128> {
+    vertices
+        .iter()
+        .zip(vertices.iter().cycle().skip(1))
+        .try_fold(0_i128, |area, (a, b)| {
+            let a = Vector2::new(a.x, a.y);
+            area.checked_add(a.cross(&Vector2::new(b.x, b.y)))
+        })
+}
+
+/// The amount of lattice points on the edges of the polygon through the vertices. An edge
+/// passes through as many lattice points as the greatest common divisor of its extents, not
+/// counting its start.
+pub fn boundary_points(vertices: &[Point2]) -> Option<u128> {
+    vertices
+        .iter()
+        .zip(vertices.iter().cycle().skip(1))
+        .try_fold(0_u128, |points, (a, b)| {
+            let dx = (b.x as i128 - a.x as i128).unsigned_abs() as u64;
+            let dy = (b.y as i128 - a.y as i128).unsigned_abs() as u64;
+            points.checked_add(math::gcd(dx, dy) as u128)
+        })
+}
+
+/// The amount of lattice points strictly inside a simple polygon, by Pick's theorem: the area is
+/// `interior + boundary / 2 - 1`. A polygon without area has no interior. Returns `None` when the
+/// count does not fit, or when the polygon folds back over itself so that its boundary is too
+/// long for its area.
+pub fn interior_points(vertices: &[Point2]) -> Option<u128> {
+    let twice_area = twice_signed_area(vertices)?.unsigned_abs();
+    if twice_area == 0 {
+        return Some(0);
+    }
+
+    Some(
+        twice_area
+            .checked_add(2)?
+            .checked_sub(boundary_points(vertices)?)?
+            / 2,
+    )
+}
+
+/// An exact fraction, always kept in lowest terms with a positive denominator. Neither part is
+/// ever `i128::MIN`, which has no positive counterpart, so fractions can always be negated and
+/// reduced. Arithmetic that would reach it fails like any other overflow.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Rational {
+    numerator: i128,
+    denominator: i128,
+}
+
+impl Rational {
+    /// Returns `None` for a denominator of 0, or when either part is `i128::MIN`.
+    pub fn new(numerator: i128, denominator: i128) -> Option<Rational> {
+        if denominator == 0 || numerator == i128::MIN || denominator == i128::MIN {
+            return None;
+        }
+
+        let (divisor, _, _) = math::extended_gcd(numerator, denominator)?;
+        let sign = denominator.signum();
+
+        Some(Rational {
+            numerator: sign * (numerator / divisor),
+            denominator: sign * (denominator / divisor),
+        })
+    }
+
+    /// Returns `None` for `i128::MIN`.
+    pub fn integer(value: i128) -> Option<Rational> {
+        Rational::new(value, 1)
+    }
+
+    pub fn numerator(&self) -> i128 {
+        self.numerator
+    }
+
+    pub fn denominator(&self) -> i128 {
+        self.denominator
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.numerator < 0
+    }
+
+    /// The value as an integer, if it is one.
+    pub fn to_integer(self) -> Option<i128> {
+        (self.denominator == 1).then_some(self.numerator)
+    }
+
+    pub fn checked_add(&self, other: &Rational) -> Option<Rational> {
+        let numerator = self
+            .numerator
+            .checked_mul(other.denominator)?
+            .checked_add(other.numerator.checked_mul(self.denominator)?)?;
+
+        Rational::new(numerator, self.denominator.checked_mul(other.denominator)?)
+    }
+
+    pub fn checked_mul(&self, other: &Rational) -> Option<Rational> {
+        Rational::new(
+            self.numerator.checked_mul(other.numerator)?,
+            self.denominator.checked_mul(other.denominator)?,
+        )
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Rational) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Compares the fractions without multiplying them out, by comparing their integer parts and
+/// then the reciprocals of what remains, so that no comparison overflows.
+impl Ord for Rational {
+    fn cmp(&self, other: &Rational) -> Ordering {
+        let (mut a, mut b) = (
+            (self.numerator, self.denominator),
+            (other.numerator, other.denominator),
+        );
+        // Taking reciprocals reverses the order, so it is flipped after every step.
+        let mut is_flipped = false;
+        let flip = |ordering: Ordering, is_flipped: bool| {
+            if is_flipped {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        };
+
+        loop {
+            let (a_integer, b_integer) = (a.0.div_euclid(a.1), b.0.div_euclid(b.1));
+            if a_integer != b_integer {
+                return flip(a_integer.cmp(&b_integer), is_flipped);
+            }
+
+            let (a_remainder, b_remainder) = (a.0.rem_euclid(a.1), b.0.rem_euclid(b.1));
+            match (a_remainder, b_remainder) {
+                (0, 0) => return Ordering::Equal,
+                (0, _) => return flip(Ordering::Less, is_flipped),
+                (_, 0) => return flip(Ordering::Greater, is_flipped),
+                _ => {}
+            }
+
+            (a, b) = ((a.1, a_remainder), (b.1, b_remainder));
+            is_flipped = !is_flipped;
+        }
+    }
+}
+
+/// How two lines meet. A crossing is given by how many direction vectors along each line it
+/// lies, so that callers can tell whether it lies on a ray or a segment.
+#[derive(PartialEq, Debug)]
+pub enum Intersection {
+    Crossing {
+        along_first: Rational,
+        along_second: Rational,
+    },
+    Parallel,
+    Coincident,
+    // Lines in three dimensions that are not parallel and do not meet.
+    Skew,
+}
+
+/// The points `origin + t * direction` for every rational `t`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Line2 {
+    pub origin: Point2,
+    pub direction: Vector2,
+}
+
+impl Line2 {
+    /// Solves `origin + t * direction = other.origin + s * other.direction` for `t` and `s` by
+    /// Cramer's rule. Fails for a zero direction, or when the exact solution does not fit.
+    pub fn intersect(&self, other: &Line2) -> Result<Intersection, String> {
+        if self.direction == Vector2::new(0, 0) || other.direction == Vector2::new(0, 0) {
+            return Err("line has no direction".to_string());
+        }
+
+        let overflow = || "overflow: line intersection does not fit in i128".to_string();
+        let offset = Vector2Wide::between(self.origin, other.origin);
+        let denominator = self.direction.cross(&other.direction);
+
+        if denominator == 0 {
+            return Ok(match offset.cross(&self.direction).ok_or_else(overflow)? {
+                0 => Intersection::Coincident,
+                _ => Intersection::Parallel,
+            });
+        }
+
+        let along_first = offset.cross(&other.direction).ok_or_else(overflow)?;
+        let along_second = offset.cross(&self.direction).ok_or_else(overflow)?;
+
+        Ok(Intersection::Crossing {
+            along_first: Rational::new(along_first, denominator).ok_or_else(overflow)?,
+            along_second: Rational::new(along_second, denominator).ok_or_else(overflow)?,
+        })
+    }
+
+    /// The point `t` direction vectors along the line, as exact coordinates.
+    pub fn point_at(&self, t: &Rational) -> Option<[Rational; 2]> {
+        Some([
+            Rational::integer(self.origin.x as i128)?
+                .checked_add(&t.checked_mul(&Rational::integer(self.direction.x as i128)?)?)?,
+            Rational::integer(self.origin.y as i128)?
+                .checked_add(&t.checked_mul(&Rational::integer(self.direction.y as i128)?)?)?,
+        ])
+    }
+}
+
+/// The points `origin + t * direction` for every rational `t`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Line3 {
+    pub origin: Point3,
+    pub direction: Vector3,
+}
+
+impl Line3 {
+    /// With `n` the cross product of both directions, lines that are not parallel meet when the
+    /// offset between their origins is perpendicular to `n`. Crossing the offset with either
+    /// direction and projecting onto `n` then gives how far along the other line they meet.
+    /// Fails for a zero direction, or when the exact solution does not fit.
+    pub fn intersect(&self, other: &Line3) -> Result<Intersection, String> {
+        let zero = Vector3::new(0, 0, 0);
+        if self.direction == zero || other.direction == zero {
+            return Err("line has no direction".to_string());
+        }
+
+        let overflow = || "overflow: line intersection does not fit in i128".to_string();
+        let offset = Vector3Wide::between(self.origin, other.origin);
+        let first = Vector3Wide::from(self.direction);
+        let second = Vector3Wide::from(other.direction);
+        let normal = first.cross(&second).ok_or_else(overflow)?;
+
+        if normal.is_zero() {
+            return Ok(match offset.cross(&first).ok_or_else(overflow)?.is_zero() {
+                true => Intersection::Coincident,
+                false => Intersection::Parallel,
+            });
+        }
+
+        if offset.dot(&normal).ok_or_else(overflow)? != 0 {
+            return Ok(Intersection::Skew);
+        }
+
+        let denominator = normal.dot(&normal).ok_or_else(overflow)?;
+        let along_first = offset
+            .cross(&second)
+            .and_then(|cross| cross.dot(&normal))
+            .ok_or_else(overflow)?;
+        let along_second = offset
+            .cross(&first)
+            .and_then(|cross| cross.dot(&normal))
+            .ok_or_else(overflow)?;
+
+        Ok(Intersection::Crossing {
+            along_first: Rational::new(along_first, denominator).ok_or_else(overflow)?,
+            along_second: Rational::new(along_second, denominator).ok_or_else(overflow)?,
+        })
+    }
+}
+
+/// A vector between two points, which needs more bits than their coordinates.
+struct Vector2Wide {
+    x: i128,
+    y: i128,
+}
+
+impl Vector2Wide {
+    fn between(from: Point2, to: Point2) -> Vector2Wide {
+        Vector2Wide {
+            x: to.x as i128 - from.x as i128,
+            y: to.y as i128 - from.y as i128,
+        }
+    }
+
+    fn cross(&self, other: &Vector2) -> Option<i128> {
+        self.x
+            .checked_mul(other.y as i128)?
+            .checked_sub(self.y.checked_mul(other.x as i128)?)
+    }
+}
+
+struct Vector3Wide {
+    x: i128,
+    y: i128,
+    z: i128,
+}
+
+impl Vector3Wide {
+    fn between(from: Point3, to: Point3) -> Vector3Wide {
+        Vector3Wide {
+            x: to.x as i128 - from.x as i128,
+            y: to.y as i128 - from.y as i128,
+            z: to.z as i128 - from.z as i128,
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.x == 0 && self.y == 0 && self.z == 0
+    }
+
+    fn cross(&self, other: &Vector3Wide) -> Option<Vector3Wide> {
+        let component =
+            |a: i128, b: i128, c: i128, d: i128| a.checked_mul(b)?.checked_sub(c.checked_mul(d)?);
+
+        Some(Vector3Wide {
+            x: component(self.y, other.z, self.z, other.y)?,
+            y: component(self.z, other.x, self.x, other.z)?,
+            z: component(self.x, other.y, self.y, other.x)?,
+        })
+    }
+
+    fn dot(&self, other: &Vector3Wide) -> Option<i128> {
+        self.x
+            .checked_mul(other.x)?
+            .checked_add(self.y.checked_mul(other.y)?)?
+            .checked_add(self.z.checked_mul(other.z)?)
+    }
+}
+
+impl From<Vector3> for Vector3Wide {
+    fn from(vector: Vector3) -> Vector3Wide {
+        Vector3Wide {
+            x: vector.x as i128,
+            y: vector.y as i128,
+            z: vector.z as i128,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rational(numerator: i128, denominator: i128) -> Rational {
+        Rational::new(numerator, denominator).expect("denominator is not 0")
+    }
+
+    #[test]
+    fn points_and_vectors() {
+        let point = Point2::new(3, -4);
+        let vector = Vector2::new(-1, 2);
+        assert_eq!(point + vector * 3, Point2::new(0, 2));
+        assert_eq!(Point2::new(0, 2) - point, Vector2::new(-3, 6));
+        assert_eq!(point.checked_add(&vector), Some(point + vector));
+        assert_eq!(
+            Point2::new(i64::MAX, 0).checked_add(&Vector2::new(1, 0)),
+            None
+        );
+        assert_eq!(
+            Point2::new(i64::MIN, 0).checked_sub(&Point2::new(1, 0)),
+            None
+        );
+        assert_eq!(Vector2::new(0, i64::MAX).checked_mul(2), None);
+
+        let point = Point3::new(1, 2, 3);
+        let vector = Vector3::new(0, -1, 2);
+        assert_eq!(point + vector * 2, Point3::new(1, 0, 7));
+        assert_eq!(Point3::new(1, 0, 7) - point, Vector3::new(0, -2, 4));
+        assert_eq!(Point3::new(0, 0, i64::MAX).checked_add(&vector), None);
+        assert_eq!(Point3::new(0, 0, i64::MIN).checked_sub(&point), None);
+        assert_eq!(Vector3::new(i64::MIN, 0, 0).checked_mul(-1), None);
+    }
+
+    #[test]
+    fn polygons() {
+        let square = [
+            Point2::new(0, 0),
+            Point2::new(4, 0),
+            Point2::new(4, 4),
+            Point2::new(0, 4),
+        ];
+        assert_eq!(twice_signed_area(&square), Some(32));
+        assert_eq!(boundary_points(&square), Some(16));
+        assert_eq!(interior_points(&square), Some(9));
+
+        let clockwise: Vec<Point2> = square.iter().rev().copied().collect();
+        assert_eq!(twice_signed_area(&clockwise), Some(-32));
+        assert_eq!(interior_points(&clockwise), Some(9));
+
+        let triangle = [Point2::new(0, 0), Point2::new(3, 0), Point2::new(0, 3)];
+        assert_eq!(twice_signed_area(&triangle), Some(9));
+        assert_eq!(boundary_points(&triangle), Some(9));
+        assert_eq!(interior_points(&triangle), Some(1));
+
+        // A dug out trench of day 18 style, with a notch cut into its side.
+        let notched = [
+            Point2::new(0, 0),
+            Point2::new(6, 0),
+            Point2::new(6, 5),
+            Point2::new(4, 5),
+            Point2::new(4, 2),
+            Point2::new(2, 2),
+            Point2::new(2, 5),
+            Point2::new(0, 5),
+        ];
+        let (interior, boundary) = (
+            interior_points(&notched).expect("interior"),
+            boundary_points(&notched).expect("boundary"),
+        );
+        assert_eq!(boundary, 28);
+        assert_eq!(interior + boundary, 7 * 6 - 3);
+
+        let line = [Point2::new(0, 0), Point2::new(5, 0)];
+        assert_eq!(twice_signed_area(&line), Some(0));
+        assert_eq!(interior_points(&line), Some(0));
+        assert_eq!(twice_signed_area(&[]), Some(0));
+
+        let folded = [
+            Point2::new(0, 0),
+            Point2::new(10, 0),
+            Point2::new(1, 0),
+            Point2::new(1, 1),
+            Point2::new(0, 1),
+        ];
+        assert_eq!(interior_points(&folded), None);
+    }
+
+    #[test]
+    fn rationals() {
+        assert_eq!(rational(6, -4), rational(-3, 2));
+        assert_eq!(rational(-3, 2).denominator(), 2);
+        assert_eq!(Rational::integer(0), Some(rational(0, -5)));
+        assert_eq!(Rational::new(1, 0), None);
+        assert_eq!(rational(10, 5).to_integer(), Some(2));
+        assert_eq!(rational(1, 3).to_integer(), None);
+
+        assert_eq!(
+            rational(1, 6).checked_add(&rational(1, 3)),
+            Some(rational(1, 2))
+        );
+        assert_eq!(
+            rational(2, 3).checked_mul(&rational(-3, 4)),
+            Some(rational(-1, 2))
+        );
+        assert_eq!(rational(i128::MAX, 1).checked_add(&rational(1, 1)), None);
+
+        // i128::MIN has no positive counterpart, so it is never a part of a fraction.
+        assert_eq!(Rational::new(i128::MIN, 1), None);
+        assert_eq!(Rational::new(1, i128::MIN), None);
+        assert_eq!(Rational::integer(i128::MIN), None);
+        assert_eq!(
+            rational(i128::MIN + 1, 1).checked_add(&rational(-1, 1)),
+            None
+        );
+        assert_eq!(
+            rational(i128::MIN + 1, 1).checked_mul(&rational(-1, 1)),
+            Some(rational(i128::MAX, 1))
+        );
+        assert_eq!(rational(i128::MIN + 2, 2), rational(i128::MIN / 2 + 1, 1));
+
+        let ordered = [
+            rational(-7, 2),
+            rational(-3, 1),
+            rational(-1, 3),
+            rational(0, 1),
+            rational(2, 7),
+            rational(1, 3),
+            rational(3, 8),
+            rational(5, 13),
+            rational(2, 5),
+            rational(1, 1),
+            rational(i128::MAX, i128::MAX - 1),
+            rational(i128::MAX - 1, i128::MAX - 2),
+        ];
+        for (i, a) in ordered.iter().enumerate() {
+            for (j, b) in ordered.iter().enumerate() {
+                assert_eq!(a.cmp(b), i.cmp(&j), "{:?} against {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn lines_in_the_plane() {
+        let line = |x, y, dx, dy| Line2 {
+            origin: Point2::new(x, y),
+            direction: Vector2::new(dx, dy),
+        };
+
+        // The first hailstones of day 24, crossing ahead of the first and behind the second.
+        let a = line(19, 13, -2, 1);
+        let b = line(18, 19, -1, -1);
+        let Ok(Intersection::Crossing {
+            along_first,
+            along_second,
+        }) = a.intersect(&b)
+        else {
+            panic!("hailstones cross");
+        };
+        assert_eq!(along_first, rational(7, 3));
+        assert_eq!(along_second, rational(11, 3));
+        assert_eq!(
+            a.point_at(&along_first),
+            Some([rational(43, 3), rational(46, 3)])
+        );
+        assert_eq!(b.point_at(&along_second), a.point_at(&along_first));
+
+        // The paths cross where the hailstone of `a` has already been.
+        let behind = line(20, 19, 1, -5).intersect(&a);
+        let Ok(Intersection::Crossing {
+            along_first,
+            along_second,
+        }) = behind
+        else {
+            panic!("lines cross");
+        };
+        assert!(!along_first.is_negative() && along_second.is_negative());
+
+        assert_eq!(a.intersect(&line(0, 0, 4, -2)), Ok(Intersection::Parallel));
+        assert_eq!(
+            a.intersect(&line(15, 15, 6, -3)),
+            Ok(Intersection::Coincident)
+        );
+        assert!(a.intersect(&line(0, 0, 0, 0)).is_err());
+
+        let far = line(i64::MIN, i64::MAX, i64::MAX, 1);
+        let near = line(i64::MAX, i64::MIN, i64::MAX, i64::MAX);
+        assert!(far.intersect(&near).is_err());
+    }
+
+    #[test]
+    fn lines_in_space() {
+        let line = |x, y, z, dx, dy, dz| Line3 {
+            origin: Point3::new(x, y, z),
+            direction: Vector3::new(dx, dy, dz),
+        };
+
+        let a = line(0, 0, 0, 1, 1, 1);
+        let b = line(2, 0, 4, 0, 1, -1);
+        assert_eq!(
+            a.intersect(&b),
+            Ok(Intersection::Crossing {
+                along_first: rational(2, 1),
+                along_second: rational(2, 1),
+            })
+        );
+
+        let c = line(1, 0, 0, 0, 1, 0);
+        assert_eq!(a.intersect(&c), Ok(Intersection::Skew));
+
+        assert_eq!(
+            a.intersect(&line(1, 0, 0, 2, 2, 2)),
+            Ok(Intersection::Parallel)
+        );
+        assert_eq!(
+            a.intersect(&line(3, 3, 3, -1, -1, -1)),
+            Ok(Intersection::Coincident)
+        );
+        assert!(a.intersect(&line(1, 1, 1, 0, 0, 0)).is_err());
+
+        let half_way = line(1, 0, 0, -1, 1, 0).intersect(&line(0, 0, 0, 1, 1, 0));
+        assert_eq!(
+            half_way,
+            Ok(Intersection::Crossing {
+                along_first: rational(1, 2),
+                along_second: rational(1, 2),
+            })
+        );
+    }
+}
@@ -0,0 +1,603 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+
+/// The index of a node, handed out in the order in which nodes are interned.
+pub type NodeId = usize;
+
+pub struct Edge<W> {
+    pub from: NodeId,
+    pub to: NodeId,
+    pub weight: W,
+}
+
+/// A directed graph whose nodes are interned by name. Undirected graphs add every edge in both
+/// directions.
+pub struct Graph<N, W> {
+    names: Vec<N>,
+    ids: HashMap<N, NodeId>,
+    edges: Vec<Edge<W>>,
+    // For every node, the edges leaving and entering it, as indices into `edges`.
+    outgoing: Vec<Vec<usize>>,
+    incoming: Vec<Vec<usize>>,
+}
+
+/// A set of edges whose removal splits the nodes in two, and the nodes on the side of the first
+/// node.
+pub struct Cut {
+    pub weight: u64,
+    pub is_first_side: Vec<bool>,
+}
+
+impl<N: Eq + Hash + Clone, W> Graph<N, W> {
+    pub fn new() -> Graph<N, W> {
+        Graph {
+            names: vec![],
+            ids: HashMap::new(),
+            edges: vec![],
+            outgoing: vec![],
+            incoming: vec![],
+        }
+    }
+
+    /// The id of the node with the given name, adding the node if it is new.
+    pub fn intern(&mut self, name: N) -> NodeId {
+        if let Some(&id) = self.ids.get(&name) {
+            return id;
+        }
+
+        let id = self.names.len();
+        self.names.push(name.clone());
+        let _ = self.ids.insert(name, id);
+        self.outgoing.push(vec![]);
+        self.incoming.push(vec![]);
+
+        id
+    }
+
+    pub fn id(&self, name: &N) -> Option<NodeId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, node: NodeId) -> &N {
+        &self.names[node]
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn nodes(&self) -> std::ops::Range<NodeId> {
+        0..self.names.len()
+    }
+
+    pub fn edges(&self) -> &[Edge<W>] {
+        &self.edges
+    }
+
+    pub fn add_edge(&mut self, from: NodeId, to: NodeId, weight: W) {
+        self.outgoing[from].push(self.edges.len());
+        self.incoming[to].push(self.edges.len());
+        self.edges.push(Edge { from, to, weight });
+    }
+
+    pub fn add_undirected_edge(&mut self, a: NodeId, b: NodeId, weight: W)
+    where
+        W: Clone,
+    {
+        self.add_edge(a, b, weight.clone());
+        self.add_edge(b, a, weight);
+    }
+
+    /// The edges leaving a node, in the order in which they were added.
+    pub fn outgoing(&self, node: NodeId) -> impl Iterator<Item = &Edge<W>> {
+        self.outgoing[node].iter().map(|&edge| &self.edges[edge])
+    }
+
+    /// The amount of edges on the shortest path from the start to every node, if it is reachable.
+    pub fn bfs(&self, start: NodeId) -> Vec<Option<usize>> {
+        let mut distances = vec![None; self.len()];
+        let mut queue = VecDeque::from([start]);
+        distances[start] = Some(0);
+
+        while let Some(node) = queue.pop_front() {
+            let distance = distances[node].expect("queued nodes have a distance");
+            for edge in self.outgoing(node) {
+                if distances[edge.to].is_none() {
+                    distances[edge.to] = Some(distance + 1);
+                    queue.push_back(edge.to);
+                }
+            }
+        }
+
+        distances
+    }
+
+    /// Orders the nodes so that every edge points forward, taking nodes by id where the order is
+    /// free. Returns `None` when the graph has a cycle.
+    pub fn topological_order(&self) -> Option<Vec<NodeId>> {
+        let mut in_degrees: Vec<usize> = self.incoming.iter().map(Vec::len).collect();
+        let mut ready: BinaryHeap<Reverse<NodeId>> = self
+            .nodes()
+            .filter(|&node| in_degrees[node] == 0)
+            .map(Reverse)
+            .collect();
+        let mut order = vec![];
+
+        while let Some(Reverse(node)) = ready.pop() {
+            order.push(node);
+            for edge in self.outgoing(node) {
+                in_degrees[edge.to] -= 1;
+                if in_degrees[edge.to] == 0 {
+                    ready.push(Reverse(edge.to));
+                }
+            }
+        }
+
+        (order.len() == self.len()).then_some(order)
+    }
+
+    /// Groups the nodes that can all reach each other, using Tarjan's algorithm. Components come
+    /// in reverse topological order: no component has an edge to a component after it.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<NodeId>> {
+        let mut indices: Vec<Option<usize>> = vec![None; self.len()];
+        let mut low_links = vec![0; self.len()];
+        let mut on_stack = vec![false; self.len()];
+        let mut stack = vec![];
+        let mut components = vec![];
+        let mut next_index = 0;
+
+        for root in self.nodes() {
+            if indices[root].is_some() {
+                continue;
+            }
+
+            // The nodes being visited, with the position of the next edge to follow from each.
+            let mut visiting = vec![(root, 0)];
+            indices[root] = Some(next_index);
+            low_links[root] = next_index;
+            next_index += 1;
+            stack.push(root);
+            on_stack[root] = true;
+
+            while let Some(&(node, next_edge)) = visiting.last() {
+                if let Some(&edge) = self.outgoing[node].get(next_edge) {
+                    visiting.last_mut().expect("node is being visited").1 += 1;
+                    let to = self.edges[edge].to;
+
+                    match indices[to] {
+                        None => {
+                            indices[to] = Some(next_index);
+                            low_links[to] = next_index;
+                            next_index += 1;
+                            stack.push(to);
+                            on_stack[to] = true;
+                            visiting.push((to, 0));
+                        }
+                        Some(index) if on_stack[to] => {
+                            low_links[node] = low_links[node].min(index);
+                        }
+                        Some(_) => {}
+                    }
+                    continue;
+                }
+
+                let _ = visiting.pop();
+                if let Some(&(parent, _)) = visiting.last() {
+                    low_links[parent] = low_links[parent].min(low_links[node]);
+                }
+
+                if Some(low_links[node]) == indices[node] {
+                    let mut component = vec![];
+                    loop {
+                        let member = stack.pop().expect("node is on the stack");
+                        on_stack[member] = false;
+                        component.push(member);
+                        if member == node {
+                            break;
+                        }
+                    }
+                    components.push(component);
+                }
+            }
+        }
+
+        components
+    }
+}
+
+impl<N: Eq + Hash + Clone, W> Default for Graph<N, W> {
+    fn default() -> Self {
+        Graph::new()
+    }
+}
+
+impl<N: Eq + Hash + Clone> Graph<N, u64> {
+    /// The length of the shortest path from the start to every node, if it is reachable. Paths
+    /// whose length does not fit in a u64 are ignored.
+    pub fn dijkstra(&self, start: NodeId) -> Vec<Option<u64>> {
+        let mut distances = vec![None; self.len()];
+        let mut queue = BinaryHeap::from([Reverse((0_u64, start))]);
+
+        while let Some(Reverse((distance, node))) = queue.pop() {
+            if distances[node].is_some() {
+                continue;
+            }
+            distances[node] = Some(distance);
+
+            for edge in self.outgoing(node) {
+                if let Some(next_distance) = distance.checked_add(edge.weight) {
+                    if distances[edge.to].is_none() {
+                        queue.push(Reverse((next_distance, edge.to)));
+                    }
+                }
+            }
+        }
+
+        distances
+    }
+
+    /// The length of the shortest path from the start to the goal, and the nodes along it. The
+    /// heuristic must never overestimate the remaining length to the goal; a heuristic of zero
+    /// searches like Dijkstra's algorithm. A heuristic that is not also consistent may find a
+    /// shorter path to a node that was already expanded, which then is expanded again.
+    pub fn a_star(
+        &self,
+        start: NodeId,
+        goal: NodeId,
+        heuristic: impl Fn(NodeId) -> u64,
+    ) -> Option<(u64, Vec<NodeId>)> {
+        let mut distances: Vec<Option<u64>> = vec![None; self.len()];
+        let mut previous: Vec<Option<NodeId>> = vec![None; self.len()];
+        let mut queue = BinaryHeap::from([Reverse((heuristic(start), 0_u64, start))]);
+        distances[start] = Some(0);
+
+        while let Some(Reverse((_, distance, node))) = queue.pop() {
+            // A node is queued again whenever a shorter path to it is found.
+            if distances[node] != Some(distance) {
+                continue;
+            }
+
+            if node == goal {
+                let mut path = vec![goal];
+                while let Some(node) = previous[*path.last().expect("path has a node")] {
+                    path.push(node);
+                }
+                path.reverse();
+
+                return Some((distance, path));
+            }
+
+            for edge in self.outgoing(node) {
+                let Some(next_distance) = distance.checked_add(edge.weight) else {
+                    continue;
+                };
+
+                if distances[edge.to].is_none_or(|known| next_distance < known) {
+                    distances[edge.to] = Some(next_distance);
+                    previous[edge.to] = Some(node);
+                    let estimate = next_distance.saturating_add(heuristic(edge.to));
+                    queue.push(Reverse((estimate, next_distance, edge.to)));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// The length of the longest path from the start to the goal that visits no node twice. This
+    /// tries every path, so it is only feasible for small graphs or graphs with few branches.
+    pub fn longest_simple_path(&self, start: NodeId, goal: NodeId) -> Option<u64> {
+        let mut on_path = vec![false; self.len()];
+        // The nodes on the path, with the position of the next edge to follow from each and the
+        // length of the path up to the node.
+        let mut path = vec![(start, 0, 0_u64)];
+        let mut longest = None;
+        on_path[start] = true;
+
+        while let Some(&(node, next_edge, length)) = path.last() {
+            if node == goal {
+                longest = longest.max(Some(length));
+            } else if let Some(&edge) = self.outgoing[node].get(next_edge) {
+                path.last_mut().expect("node is on the path").1 += 1;
+                let edge = &self.edges[edge];
+
+                if !on_path[edge.to] {
+                    if let Some(next_length) = length.checked_add(edge.weight) {
+                        on_path[edge.to] = true;
+                        path.push((edge.to, 0, next_length));
+                    }
+                }
+                continue;
+            }
+
+            on_path[node] = false;
+            let _ = path.pop();
+        }
+
+        longest
+    }
+
+    /// Finds a global minimum cut of an undirected graph, with edge weights as capacities. The
+    /// maximum flow from the first node to every other node gives the smallest cut separating the
+    /// two, and the smallest of these cuts is a global minimum. Nodes still reachable from the
+    /// first node in the residual graph of that flow form its side of the cut. Returns `None` for
+    /// graphs with fewer than two nodes.
+    pub fn minimum_cut(&self) -> Option<Cut> {
+        let source = 0;
+        let mut best: Option<Cut> = None;
+
+        for sink in 1..self.len() {
+            // A flow that reaches the weight of the best cut so far cannot improve on it.
+            let limit = best.as_ref().map_or(u64::MAX, |cut| cut.weight);
+            let (weight, is_first_side) = self.maximum_flow(source, sink, limit);
+
+            if weight < limit {
+                best = Some(Cut {
+                    weight,
+                    is_first_side,
+                });
+            }
+        }
+
+        best
+    }
+
+    /// Pushes flow along shortest augmenting paths from source to sink, until none is left or
+    /// the flow reaches the limit. Returns the flow and the nodes reachable from the source in
+    /// the residual graph.
+    fn maximum_flow(&self, source: NodeId, sink: NodeId, limit: u64) -> (u64, Vec<bool>) {
+        let mut flow_per_edge = vec![0_u64; self.edges.len()];
+        let mut flow: u64 = 0;
+
+        loop {
+            let via_edge = self.search_residual(source, &flow_per_edge);
+            let reachable: Vec<bool> = self
+                .nodes()
+                .map(|node| node == source || via_edge[node].is_some())
+                .collect();
+
+            if !reachable[sink] || flow >= limit {
+                return (flow, reachable);
+            }
+
+            // Walks the augmenting path back from the sink, first to find its bottleneck and then
+            // to push that much flow along it.
+            let mut bottleneck = u64::MAX;
+            let mut cursor = sink;
+            while cursor != source {
+                let (edge, is_forward) = via_edge[cursor].expect("reached node has an edge");
+                let edge_data = &self.edges[edge];
+                let residual = if is_forward {
+                    edge_data.weight - flow_per_edge[edge]
+                } else {
+                    flow_per_edge[edge]
+                };
+                bottleneck = bottleneck.min(residual);
+                cursor = if is_forward {
+                    edge_data.from
+                } else {
+                    edge_data.to
+                };
+            }
+
+            let mut cursor = sink;
+            while cursor != source {
+                let (edge, is_forward) = via_edge[cursor].expect("reached node has an edge");
+                if is_forward {
+                    flow_per_edge[edge] += bottleneck;
+                    cursor = self.edges[edge].from;
+                } else {
+                    flow_per_edge[edge] -= bottleneck;
+                    cursor = self.edges[edge].to;
+                }
+            }
+
+            flow = flow.saturating_add(bottleneck);
+        }
+    }
+
+    /// For every node reachable from the source in the residual graph, the edge it was reached by
+    /// and whether that edge was followed forwards.
+    fn search_residual(&self, source: NodeId, flow_per_edge: &[u64]) -> Vec<Option<(usize, bool)>> {
+        let mut via_edge = vec![None; self.len()];
+        let mut queue = VecDeque::from([source]);
+
+        while let Some(node) = queue.pop_front() {
+            let forward = self.outgoing[node]
+                .iter()
+                .filter(|&&edge| flow_per_edge[edge] < self.edges[edge].weight)
+                .map(|&edge| (edge, self.edges[edge].to, true));
+            let backward = self.incoming[node]
+                .iter()
+                .filter(|&&edge| flow_per_edge[edge] > 0)
+                .map(|&edge| (edge, self.edges[edge].from, false));
+
+            for (edge, neighbour, is_forward) in forward.chain(backward) {
+                if neighbour != source && via_edge[neighbour].is_none() {
+                    via_edge[neighbour] = Some((edge, is_forward));
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+
+        via_edge
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a graph from `from to weight` triples, interning the names in order of appearance.
+    fn directed_graph(edges: &[(&'static str, &'static str, u64)]) -> Graph<&'static str, u64> {
+        let mut graph = Graph::new();
+        for &(from, to, weight) in edges {
+            let from = graph.intern(from);
+            let to = graph.intern(to);
+            graph.add_edge(from, to, weight);
+        }
+
+        graph
+    }
+
+    fn names(graph: &Graph<&'static str, u64>, nodes: &[NodeId]) -> Vec<&'static str> {
+        nodes.iter().map(|&node| *graph.name(node)).collect()
+    }
+
+    #[test]
+    fn interning() {
+        let mut graph: Graph<String, ()> = Graph::new();
+        let a = graph.intern("a".to_string());
+        let b = graph.intern("b".to_string());
+
+        assert_eq!((a, b), (0, 1));
+        assert_eq!(graph.intern("a".to_string()), a);
+        assert_eq!(graph.id(&"b".to_string()), Some(b));
+        assert_eq!(graph.id(&"c".to_string()), None);
+        assert_eq!(graph.name(b), "b");
+        assert_eq!(graph.len(), 2);
+    }
+
+    #[test]
+    fn shortest_paths() {
+        let graph = directed_graph(&[
+            ("a", "b", 7),
+            ("a", "c", 2),
+            ("c", "b", 3),
+            ("b", "d", 1),
+            ("c", "d", 9),
+            ("e", "a", 1),
+        ]);
+        let (a, d, e) = (0, 3, 4);
+
+        assert_eq!(graph.bfs(a), vec![Some(0), Some(1), Some(1), Some(2), None]);
+        assert_eq!(
+            graph.dijkstra(a),
+            vec![Some(0), Some(5), Some(2), Some(6), None]
+        );
+
+        let (length, path) = graph.a_star(a, d, |_| 0).expect("path from a to d");
+        assert_eq!(length, 6);
+        assert_eq!(names(&graph, &path), vec!["a", "c", "b", "d"]);
+
+        // Remaining lengths to d, which never overestimate.
+        let heuristic = |node: NodeId| [6, 1, 4, 0, 7][node];
+        let (length, path) = graph.a_star(e, d, heuristic).expect("path from e to d");
+        assert_eq!(length, 7);
+        assert_eq!(names(&graph, &path), vec!["e", "a", "c", "b", "d"]);
+
+        assert_eq!(graph.a_star(d, a, |_| 0), None);
+        assert_eq!(graph.a_star(a, a, |_| 0), Some((0, vec![a])));
+
+        // This heuristic never overestimates, but it is not consistent: it makes b look closer
+        // to g from s directly than by way of a, so b is first reached by the longer path.
+        let graph = directed_graph(&[("s", "a", 1), ("s", "b", 3), ("a", "b", 1), ("b", "g", 10)]);
+        let (s, g) = (0, 3);
+        let heuristic = |node: NodeId| [0, 11, 0, 0][node];
+        assert_eq!(graph.dijkstra(s)[g], Some(12));
+        let (length, path) = graph.a_star(s, g, heuristic).expect("path from s to g");
+        assert_eq!(length, 12);
+        assert_eq!(names(&graph, &path), vec!["s", "a", "b", "g"]);
+    }
+
+    #[test]
+    fn topological_order() {
+        let graph = directed_graph(&[("d", "b", 1), ("a", "b", 1), ("b", "c", 1), ("a", "c", 1)]);
+        let order = graph.topological_order().expect("graph is acyclic");
+        assert_eq!(names(&graph, &order), vec!["d", "a", "b", "c"]);
+
+        let cyclic = directed_graph(&[("a", "b", 1), ("b", "c", 1), ("c", "a", 1), ("c", "d", 1)]);
+        assert_eq!(cyclic.topological_order(), None);
+    }
+
+    #[test]
+    fn strongly_connected_components() {
+        let graph = directed_graph(&[
+            ("a", "b", 1),
+            ("b", "c", 1),
+            ("c", "a", 1),
+            ("c", "d", 1),
+            ("d", "e", 1),
+            ("e", "d", 1),
+            ("f", "f", 1),
+            ("f", "e", 1),
+        ]);
+
+        let components: Vec<Vec<&str>> = graph
+            .strongly_connected_components()
+            .iter()
+            .map(|component| {
+                let mut component = names(&graph, component);
+                component.sort();
+                component
+            })
+            .collect();
+
+        assert_eq!(
+            components,
+            vec![vec!["d", "e"], vec!["a", "b", "c"], vec!["f"]]
+        );
+    }
+
+    #[test]
+    fn longest_simple_path() {
+        let mut graph =
+            directed_graph(&[("a", "b", 1), ("b", "d", 1), ("a", "c", 5), ("c", "d", 1)]);
+        let (a, b, d, c) = (0, 1, 2, 3);
+        graph.add_undirected_edge(b, c, 2);
+
+        assert_eq!(graph.longest_simple_path(a, d), Some(8));
+        assert_eq!(graph.longest_simple_path(d, a), None);
+        assert_eq!(graph.longest_simple_path(a, a), Some(0));
+        assert_eq!(graph.longest_simple_path(c, b), Some(2));
+    }
+
+    #[test]
+    fn minimum_cut() {
+        // Two triangles joined by a single edge, and a heavier pair of edges between them.
+        let mut graph: Graph<&str, u64> = Graph::new();
+        let edges = [
+            ("a", "b", 3),
+            ("b", "c", 3),
+            ("c", "a", 3),
+            ("x", "y", 3),
+            ("y", "z", 3),
+            ("z", "x", 3),
+            ("a", "x", 1),
+            ("b", "y", 2),
+        ];
+        for (a, b, weight) in edges {
+            let a = graph.intern(a);
+            let b = graph.intern(b);
+            graph.add_undirected_edge(a, b, weight);
+        }
+
+        let cut = graph.minimum_cut().expect("graph has two nodes");
+        assert_eq!(cut.weight, 3);
+        let first_side: Vec<&str> = graph
+            .nodes()
+            .filter(|&node| cut.is_first_side[node])
+            .map(|node| *graph.name(node))
+            .collect();
+        assert_eq!(first_side, vec!["a", "b", "c"]);
+
+        let mut single: Graph<&str, u64> = Graph::new();
+        let _ = single.intern("a");
+        assert!(single.minimum_cut().is_none());
+
+        // A node without edges is cut off for free.
+        let mut disconnected: Graph<&str, u64> = Graph::new();
+        let a = disconnected.intern("a");
+        let b = disconnected.intern("b");
+        let _ = disconnected.intern("c");
+        disconnected.add_undirected_edge(a, b, 5);
+        let cut = disconnected.minimum_cut().expect("graph has two nodes");
+        assert_eq!(cut.weight, 0);
+        assert_eq!(cut.is_first_side, vec![true, true, false]);
+    }
+}
@@ -0,0 +1,426 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+/// A cell of a grid, counted from the top left.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub struct Position {
+    pub row: usize,
+    pub column: usize,
+}
+
+impl Position {
+    pub fn new(row: usize, column: usize) -> Position {
+        Position { row, column }
+    }
+}
+
+/// A row of text whose width differs from the first row.
+#[derive(PartialEq, Debug)]
+pub struct RaggedRowError {
+    pub line_number: usize,
+    pub width: usize,
+    pub expected_width: usize,
+}
+
+impl fmt::Display for RaggedRowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {} is {} characters wide instead of {}",
+            self.line_number, self.width, self.expected_width
+        )
+    }
+}
+
+impl std::error::Error for RaggedRowError {}
+
+/// A rectangular grid of cells, stored row by row.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl Grid<char> {
+    /// Parses a grid with a cell for every character. Line endings may be `\n`, `\r\n` or `\r`,
+    /// but every row must be equally wide.
+    pub fn parse(data: &str) -> Result<Grid<char>, RaggedRowError> {
+        Grid::parse_with(data, |character| character)
+    }
+}
+
+impl<T> Grid<T> {
+    /// Parses a grid, converting every character to a cell.
+    pub fn parse_with(data: &str, cell: impl Fn(char) -> T) -> Result<Grid<T>, RaggedRowError> {
+        let data = data.replace("\r\n", "\n").replace('\r', "\n");
+
+        Grid::from_rows(
+            data.lines()
+                .map(|line| line.chars().map(&cell).collect())
+                .collect(),
+        )
+    }
+
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Grid<T>, RaggedRowError> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, |row| row.len());
+        let mut cells = Vec::with_capacity(width * height);
+
+        for (row, cells_in_row) in rows.into_iter().enumerate() {
+            if cells_in_row.len() != width {
+                return Err(RaggedRowError {
+                    line_number: row + 1,
+                    width: cells_in_row.len(),
+                    expected_width: width,
+                });
+            }
+            cells.extend(cells_in_row);
+        }
+
+        Ok(Grid {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    /// A grid of the given size with every cell set to `value`.
+    pub fn filled(width: usize, height: usize, value: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        position.row < self.height && position.column < self.width
+    }
+
+    pub fn get(&self, position: Position) -> Option<&T> {
+        self.contains(position)
+            .then(|| &self.cells[position.row * self.width + position.column])
+    }
+
+    pub fn get_mut(&mut self, position: Position) -> Option<&mut T> {
+        if !self.contains(position) {
+            return None;
+        }
+
+        Some(&mut self.cells[position.row * self.width + position.column])
+    }
+
+    pub fn row(&self, row: usize) -> Option<&[T]> {
+        (row < self.height).then(|| &self.cells[row * self.width..(row + 1) * self.width])
+    }
+
+    pub fn column(&self, column: usize) -> Option<impl Iterator<Item = &T>> {
+        (column < self.width).then(|| self.column_cells(column))
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // Not `chunks`, which would yield no rows at all for a grid of zero width.
+        (0..self.height).map(|row| &self.cells[row * self.width..(row + 1) * self.width])
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|column| self.column_cells(column))
+    }
+
+    // Not a slice from the column onwards, which would start past the end of a grid of zero
+    // height.
+    fn column_cells(&self, column: usize) -> impl Iterator<Item = &T> {
+        (0..self.height).map(move |row| &self.cells[row * self.width + column])
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        let width = self.width;
+
+        (0..self.height)
+            .flat_map(move |row| (0..width).map(move |column| Position::new(row, column)))
+    }
+
+    /// Every cell together with its position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    /// The positions of every cell that matches, row by row.
+    pub fn find_all<'a>(
+        &'a self,
+        predicate: impl Fn(&T) -> bool + 'a,
+    ) -> impl Iterator<Item = Position> + 'a {
+        self.iter()
+            .filter(move |(_, cell)| predicate(cell))
+            .map(|(position, _)| position)
+    }
+
+    /// The neighbours above, right of, below and left of a position that lie within the grid.
+    pub fn neighbours4(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        self.neighbours(position, &[(-1, 0), (0, 1), (1, 0), (0, -1)])
+    }
+
+    /// The neighbours of a position that lie within the grid, diagonals included, clockwise from
+    /// the top left.
+    pub fn neighbours8(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        self.neighbours(
+            position,
+            &[
+                (-1, -1),
+                (-1, 0),
+                (-1, 1),
+                (0, 1),
+                (1, 1),
+                (1, 0),
+                (1, -1),
+                (0, -1),
+            ],
+        )
+    }
+
+    fn neighbours(
+        &self,
+        position: Position,
+        offsets: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = Position> + '_ {
+        offsets
+            .iter()
+            .filter_map(move |&(row_offset, column_offset)| {
+                let neighbour = Position::new(
+                    position.row.checked_add_signed(row_offset)?,
+                    position.column.checked_add_signed(column_offset)?,
+                );
+
+                self.contains(neighbour).then_some(neighbour)
+            })
+    }
+
+    pub fn map<U>(&self, cell: impl Fn(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(cell).collect(),
+        }
+    }
+
+    /// Mirrors the grid along its main diagonal, turning rows into columns.
+    pub fn transpose(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        self.rebuild(self.height, self.width, |position| {
+            Position::new(position.column, position.row)
+        })
+    }
+
+    /// Rotates the grid a quarter turn, so that the left column becomes the top row.
+    pub fn rotate_clockwise(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        self.rebuild(self.height, self.width, |position| {
+            Position::new(self.height - 1 - position.column, position.row)
+        })
+    }
+
+    /// Rotates the grid a quarter turn, so that the right column becomes the top row.
+    pub fn rotate_counterclockwise(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        self.rebuild(self.height, self.width, |position| {
+            Position::new(position.column, self.width - 1 - position.row)
+        })
+    }
+
+    /// Builds a grid of the given size, taking each cell from the position in this grid that
+    /// `source` points to.
+    fn rebuild(&self, width: usize, height: usize, source: impl Fn(Position) -> Position) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let cells = (0..height)
+            .flat_map(|row| (0..width).map(move |column| Position::new(row, column)))
+            .map(|position| self[source(position)].clone())
+            .collect();
+
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+}
+
+impl<T> Index<Position> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: Position) -> &T {
+        self.get(position)
+            .unwrap_or_else(|| panic!("position {:?} is outside of the grid", position))
+    }
+}
+
+impl<T> IndexMut<Position> for Grid<T> {
+    fn index_mut(&mut self, position: Position) -> &mut T {
+        self.get_mut(position)
+            .unwrap_or_else(|| panic!("position {:?} is outside of the grid", position))
+    }
+}
+
+/// Writes the grid as lines of cells, so that a parsed grid of characters is written back as the
+/// text it came from.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "abc
+def
+";
+
+    #[test]
+    fn views() {
+        let grid = Grid::parse(EXAMPLE).expect("parsing grid");
+
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[Position::new(1, 2)], 'f');
+        assert_eq!(grid.get(Position::new(2, 0)), None);
+        assert_eq!(grid.get(Position::new(0, 3)), None);
+
+        assert_eq!(grid.row(1), Some(&['d', 'e', 'f'][..]));
+        assert_eq!(grid.row(2), None);
+        let column: Vec<char> = grid.column(1).expect("column 1").copied().collect();
+        assert_eq!(column, vec!['b', 'e']);
+        assert!(grid.column(3).is_none());
+
+        let rows: Vec<String> = grid.rows().map(|row| row.iter().collect()).collect();
+        assert_eq!(rows, vec!["abc", "def"]);
+        let columns: Vec<String> = grid.columns().map(|column| column.collect()).collect();
+        assert_eq!(columns, vec!["ad", "be", "cf"]);
+
+        let vowels: Vec<Position> = grid.find_all(|cell| "aeiou".contains(*cell)).collect();
+        assert_eq!(vowels, vec![Position::new(0, 0), Position::new(1, 1)]);
+    }
+
+    #[test]
+    fn neighbours() {
+        let grid = Grid::parse(EXAMPLE).expect("parsing grid");
+
+        let corner: Vec<char> = grid
+            .neighbours4(Position::new(0, 0))
+            .map(|position| grid[position])
+            .collect();
+        assert_eq!(corner, vec!['b', 'd']);
+
+        let centre: Vec<char> = grid
+            .neighbours8(Position::new(1, 1))
+            .map(|position| grid[position])
+            .collect();
+        assert_eq!(centre, vec!['a', 'b', 'c', 'f', 'd']);
+
+        let edge: Vec<char> = grid
+            .neighbours8(Position::new(0, 2))
+            .map(|position| grid[position])
+            .collect();
+        assert_eq!(edge, vec!['f', 'e', 'b']);
+
+        let outside: Vec<Position> = grid.neighbours4(Position::new(5, 5)).collect();
+        assert!(outside.is_empty());
+    }
+
+    #[test]
+    fn rotations() {
+        let grid = Grid::parse(EXAMPLE).expect("parsing grid");
+
+        assert_eq!(grid.transpose().to_string(), "ad\nbe\ncf\n");
+        assert_eq!(grid.rotate_clockwise().to_string(), "da\neb\nfc\n");
+        assert_eq!(grid.rotate_counterclockwise().to_string(), "cf\nbe\nad\n");
+
+        let turned_around = grid.rotate_clockwise().rotate_clockwise();
+        assert_eq!(turned_around.to_string(), "fed\ncba\n");
+        assert_eq!(turned_around.rotate_clockwise().rotate_clockwise(), grid);
+        assert_eq!(grid.rotate_clockwise().rotate_counterclockwise(), grid);
+        assert_eq!(grid.transpose().transpose(), grid);
+    }
+
+    #[test]
+    fn parsing() {
+        let grid = Grid::parse(EXAMPLE).expect("parsing grid");
+        assert_eq!(grid.to_string(), EXAMPLE);
+        assert_eq!(Grid::parse(&grid.to_string()), Ok(grid.clone()));
+
+        assert_eq!(Grid::parse("abc\r\ndef"), Ok(grid.clone()));
+        assert_eq!(Grid::parse("abc\rdef"), Ok(grid));
+
+        let digits = Grid::parse_with("12\n34", |character| character.to_digit(10));
+        let digits = digits.expect("parsing digits");
+        assert_eq!(digits[Position::new(1, 0)], Some(3));
+        assert_eq!(
+            digits.map(|digit| digit.unwrap_or(0) * 2).to_string(),
+            "24\n68\n"
+        );
+
+        let empty = Grid::parse("").expect("parsing empty grid");
+        assert_eq!((empty.width(), empty.height()), (0, 0));
+        assert_eq!(empty.rows().count(), 0);
+        assert_eq!(empty.to_string(), "");
+
+        let blank_lines = Grid::parse("\n\n").expect("parsing blank lines");
+        assert_eq!((blank_lines.width(), blank_lines.height()), (0, 2));
+        assert_eq!(blank_lines.rows().collect::<Vec<&[char]>>(), vec![&[], &[]]);
+        assert_eq!(blank_lines.to_string(), "\n\n");
+        assert_eq!(
+            Grid::parse(&blank_lines.to_string()),
+            Ok(blank_lines.clone())
+        );
+
+        // Without rows, there are still columns, each without cells.
+        let no_rows = blank_lines.transpose();
+        assert_eq!((no_rows.width(), no_rows.height()), (2, 0));
+        assert_eq!(
+            no_rows
+                .columns()
+                .map(Iterator::count)
+                .collect::<Vec<usize>>(),
+            vec![0, 0]
+        );
+        assert_eq!(no_rows.column(1).map(Iterator::count), Some(0));
+        assert_eq!(no_rows.to_string(), "");
+        assert_eq!(no_rows.rotate_clockwise(), blank_lines);
+        assert_eq!(Grid::filled(3, 0, '.').columns().count(), 3);
+
+        assert_eq!(
+            Grid::parse("ab\nc"),
+            Err(RaggedRowError {
+                line_number: 2,
+                width: 1,
+                expected_width: 2,
+            })
+        );
+    }
+}
@@ -0,0 +1,507 @@
+/// The integers from `start` up to but not including `end`. An interval whose end does not lie
+/// after its start is empty.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Interval<T> {
+    pub start: T,
+    pub end: T,
+}
+
+impl<T: Copy + Ord> Interval<T> {
+    pub fn new(start: T, end: T) -> Interval<T> {
+        Interval { start, end }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        self.start <= value && value < self.end
+    }
+
+    /// The amount of integers in the interval.
+    pub fn len(&self) -> u128
+    where
+        T: Into<i128>,
+    {
+        if self.is_empty() {
+            return 0;
+        }
+
+        (self.end.into() - self.start.into()) as u128
+    }
+
+    pub fn overlaps(&self, other: &Interval<T>) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// The integers in both intervals, if there are any.
+    pub fn intersection(&self, other: &Interval<T>) -> Option<Interval<T>> {
+        let intersection = Interval::new(self.start.max(other.start), self.end.min(other.end));
+
+        (!intersection.is_empty()).then_some(intersection)
+    }
+
+    /// The integers in this interval but not in the other, as up to two intervals in order.
+    pub fn difference(&self, other: &Interval<T>) -> Vec<Interval<T>> {
+        let Some(intersection) = self.intersection(other) else {
+            return if self.is_empty() { vec![] } else { vec![*self] };
+        };
+
+        [
+            Interval::new(self.start, intersection.start),
+            Interval::new(intersection.end, self.end),
+        ]
+        .into_iter()
+        .filter(|interval| !interval.is_empty())
+        .collect()
+    }
+
+    /// The integers before the point and the integers from the point on, if there are any.
+    pub fn split_at(&self, point: T) -> (Option<Interval<T>>, Option<Interval<T>>) {
+        let before = Interval::new(self.start, self.end.min(point));
+        let after = Interval::new(self.start.max(point), self.end);
+
+        (
+            (!before.is_empty()).then_some(before),
+            (!after.is_empty()).then_some(after),
+        )
+    }
+}
+
+/// A set of integers, kept as ordered intervals that neither overlap nor touch.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct IntervalSet<T> {
+    intervals: Vec<Interval<T>>,
+}
+
+impl<T: Copy + Ord> IntervalSet<T> {
+    pub fn new() -> IntervalSet<T> {
+        IntervalSet { intervals: vec![] }
+    }
+
+    pub fn intervals(&self) -> &[Interval<T>] {
+        &self.intervals
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// The amount of integers in the set.
+    pub fn len(&self) -> u128
+    where
+        T: Into<i128>,
+    {
+        self.intervals.iter().map(Interval::len).sum()
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        // The intervals before the value are the ones that start at or before it.
+        let index = self
+            .intervals
+            .partition_point(|interval| interval.start <= value);
+
+        index > 0 && self.intervals[index - 1].contains(value)
+    }
+
+    pub fn insert(&mut self, interval: Interval<T>) {
+        *self = self.union(&IntervalSet::from_iter([interval]));
+    }
+
+    pub fn union(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        self.intervals
+            .iter()
+            .chain(other.intervals.iter())
+            .copied()
+            .collect()
+    }
+
+    pub fn intersection(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut intervals = vec![];
+        let (mut i, mut j) = (0, 0);
+
+        while i < self.intervals.len() && j < other.intervals.len() {
+            let (a, b) = (self.intervals[i], other.intervals[j]);
+            if let Some(intersection) = a.intersection(&b) {
+                intervals.push(intersection);
+            }
+
+            // The interval that ends first cannot overlap any later interval of the other set.
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        IntervalSet { intervals }
+    }
+
+    pub fn difference(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut intervals = vec![];
+        let mut first_overlapping = 0;
+
+        for interval in self.intervals.iter() {
+            while first_overlapping < other.intervals.len()
+                && other.intervals[first_overlapping].end <= interval.start
+            {
+                first_overlapping += 1;
+            }
+
+            let mut start = interval.start;
+            for removed in other.intervals[first_overlapping..]
+                .iter()
+                .take_while(|removed| removed.start < interval.end)
+            {
+                if start < removed.start {
+                    intervals.push(Interval::new(start, removed.start));
+                }
+                start = start.max(removed.end);
+            }
+
+            if start < interval.end {
+                intervals.push(Interval::new(start, interval.end));
+            }
+        }
+
+        IntervalSet { intervals }
+    }
+
+    /// The integers before the point and the integers from the point on.
+    pub fn split_at(&self, point: T) -> (IntervalSet<T>, IntervalSet<T>) {
+        let mut before = vec![];
+        let mut after = vec![];
+
+        for interval in self.intervals.iter() {
+            let (interval_before, interval_after) = interval.split_at(point);
+            before.extend(interval_before);
+            after.extend(interval_after);
+        }
+
+        (
+            IntervalSet { intervals: before },
+            IntervalSet { intervals: after },
+        )
+    }
+}
+
+impl<T: Copy + Ord> Default for IntervalSet<T> {
+    fn default() -> Self {
+        IntervalSet::new()
+    }
+}
+
+/// Collects intervals in any order into a set, merging the ones that overlap or touch and
+/// dropping empty ones.
+impl<T: Copy + Ord> FromIterator<Interval<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Interval<T>>>(iter: I) -> Self {
+        let mut sorted: Vec<Interval<T>> = iter
+            .into_iter()
+            .filter(|interval| !interval.is_empty())
+            .collect();
+        sorted.sort_by_key(|interval| interval.start);
+
+        let mut intervals: Vec<Interval<T>> = vec![];
+        for interval in sorted {
+            match intervals.last_mut() {
+                Some(last) if interval.start <= last.end => last.end = last.end.max(interval.end),
+                _ => intervals.push(interval),
+            }
+        }
+
+        IntervalSet { intervals }
+    }
+}
+
+/// The points whose coordinates each lie in the interval of their axis.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Cuboid<T, const N: usize> {
+    pub extents: [Interval<T>; N],
+}
+
+impl<T: Copy + Ord, const N: usize> Cuboid<T, N> {
+    pub fn new(extents: [Interval<T>; N]) -> Cuboid<T, N> {
+        Cuboid { extents }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.extents.iter().any(Interval::is_empty)
+    }
+
+    pub fn contains(&self, point: [T; N]) -> bool {
+        self.extents
+            .iter()
+            .zip(point)
+            .all(|(extent, coordinate)| extent.contains(coordinate))
+    }
+
+    /// The amount of points in the cuboid, or `None` if it does not fit in a u128.
+    pub fn volume(&self) -> Option<u128>
+    where
+        T: Into<i128>,
+    {
+        self.extents
+            .iter()
+            .try_fold(1_u128, |volume, extent| volume.checked_mul(extent.len()))
+    }
+
+    pub fn overlaps(&self, other: &Cuboid<T, N>) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    pub fn intersection(&self, other: &Cuboid<T, N>) -> Option<Cuboid<T, N>> {
+        let mut extents = self.extents;
+        for (extent, other_extent) in extents.iter_mut().zip(other.extents.iter()) {
+            *extent = extent.intersection(other_extent)?;
+        }
+
+        Some(Cuboid { extents })
+    }
+
+    /// The points before the point on the given axis and the points from it on, if there are
+    /// any.
+    pub fn split_at(&self, axis: usize, point: T) -> (Option<Cuboid<T, N>>, Option<Cuboid<T, N>>) {
+        let (before, after) = self.extents[axis].split_at(point);
+        let with_extent = |extent: Interval<T>| {
+            let mut extents = self.extents;
+            extents[axis] = extent;
+            Cuboid { extents }
+        };
+
+        (before.map(with_extent), after.map(with_extent))
+    }
+
+    /// The points in this cuboid but not in the other, as at most `2 * N` disjoint cuboids. The
+    /// cuboid is cut along the faces of the overlap one axis at a time, keeping the slabs outside
+    /// of it.
+    pub fn difference(&self, other: &Cuboid<T, N>) -> Vec<Cuboid<T, N>> {
+        let Some(overlap) = self.intersection(other) else {
+            return if self.is_empty() { vec![] } else { vec![*self] };
+        };
+
+        let mut pieces = vec![];
+        let mut remainder = *self;
+
+        for axis in 0..N {
+            let (before, rest) = remainder.split_at(axis, overlap.extents[axis].start);
+            let rest = rest.expect("the overlap lies within the remainder");
+            let (inside, after) = rest.split_at(axis, overlap.extents[axis].end);
+
+            pieces.extend(before);
+            pieces.extend(after);
+            remainder = inside.expect("the overlap lies within the remainder");
+        }
+
+        pieces
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use proptest::prelude::*;
+
+    use super::*;
+
+    // Every interval of the small domain lies within it, so sets of intervals can be compared
+    // against the sets of their points.
+    const DOMAIN: std::ops::Range<i64> = -8..9;
+
+    fn interval() -> impl Strategy<Value = Interval<i64>> {
+        (DOMAIN, DOMAIN).prop_map(|(start, end)| Interval::new(start, end))
+    }
+
+    fn interval_set() -> impl Strategy<Value = IntervalSet<i64>> {
+        prop::collection::vec(interval(), 0..5).prop_map(IntervalSet::from_iter)
+    }
+
+    fn cuboid() -> impl Strategy<Value = Cuboid<i64, 3>> {
+        [interval(), interval(), interval()].prop_map(Cuboid::new)
+    }
+
+    fn points(intervals: &[Interval<i64>]) -> BTreeSet<i64> {
+        DOMAIN
+            .filter(|&value| intervals.iter().any(|interval| interval.contains(value)))
+            .collect()
+    }
+
+    fn cuboid_points(cuboids: &[Cuboid<i64, 3>]) -> BTreeSet<[i64; 3]> {
+        let mut points = BTreeSet::new();
+        for x in DOMAIN {
+            for y in DOMAIN {
+                for z in DOMAIN {
+                    if cuboids.iter().any(|cuboid| cuboid.contains([x, y, z])) {
+                        let _ = points.insert([x, y, z]);
+                    }
+                }
+            }
+        }
+
+        points
+    }
+
+    /// The intervals of a set are ordered, not empty, and neither overlap nor touch.
+    fn is_normalised(set: &IntervalSet<i64>) -> bool {
+        set.intervals().iter().all(|interval| !interval.is_empty())
+            && set
+                .intervals()
+                .windows(2)
+                .all(|pair| pair[0].end < pair[1].start)
+    }
+
+    #[test]
+    fn intervals() {
+        let interval = Interval::new(2_u64, 7);
+
+        assert_eq!(interval.len(), 5);
+        assert!(interval.contains(2) && !interval.contains(7));
+        assert_eq!(
+            interval.intersection(&Interval::new(5, 10)),
+            Some(Interval::new(5, 7))
+        );
+        assert_eq!(interval.intersection(&Interval::new(7, 10)), None);
+        assert_eq!(
+            interval.difference(&Interval::new(3, 5)),
+            vec![Interval::new(2, 3), Interval::new(5, 7)]
+        );
+        assert_eq!(interval.split_at(2), (None, Some(Interval::new(2, 7))));
+        assert_eq!(Interval::new(5_u64, 2).len(), 0);
+
+        let set: IntervalSet<u64> = [
+            Interval::new(5, 8),
+            Interval::new(0, 2),
+            Interval::new(2, 3),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(set.intervals(), &[Interval::new(0, 3), Interval::new(5, 8)]);
+        assert_eq!(set.len(), 6);
+        assert!(set.contains(7) && !set.contains(4));
+    }
+
+    #[test]
+    fn cuboids() {
+        let cuboid = Cuboid::new([Interval::new(0, 4), Interval::new(0, 3)]);
+        let hole = Cuboid::new([Interval::new(1, 2), Interval::new(1, 2)]);
+
+        assert_eq!(cuboid.volume(), Some(12));
+        assert_eq!(cuboid.intersection(&hole), Some(hole));
+        let pieces = cuboid.difference(&hole);
+        assert_eq!(pieces.len(), 4);
+        assert_eq!(
+            pieces
+                .iter()
+                .map(|piece| piece.volume().unwrap_or(0))
+                .sum::<u128>(),
+            11
+        );
+
+        let far_away = Cuboid::new([Interval::new(10, 14), Interval::new(0, 3)]);
+        assert!(!cuboid.overlaps(&far_away));
+        assert_eq!(cuboid.difference(&far_away), vec![cuboid]);
+
+        let huge = Cuboid::new([Interval::new(i64::MIN, i64::MAX); 3]);
+        assert_eq!(huge.volume(), None);
+    }
+
+    proptest! {
+        #[test]
+        fn interval_arithmetic(a in interval(), b in interval(), point in DOMAIN) {
+            let (points_a, points_b) = (points(&[a]), points(&[b]));
+
+            prop_assert_eq!(a.len() as usize, points_a.len());
+            let intersection: Vec<Interval<i64>> = a.intersection(&b).into_iter().collect();
+            prop_assert_eq!(
+                points(&intersection),
+                points_a.intersection(&points_b).copied().collect()
+            );
+            prop_assert_eq!(
+                points(&a.difference(&b)),
+                points_a.difference(&points_b).copied().collect()
+            );
+
+            let (before, after) = a.split_at(point);
+            prop_assert_eq!(
+                points(&before.into_iter().collect::<Vec<_>>()),
+                points_a.iter().copied().filter(|&value| value < point).collect()
+            );
+            prop_assert_eq!(
+                points(&after.into_iter().collect::<Vec<_>>()),
+                points_a.iter().copied().filter(|&value| value >= point).collect()
+            );
+        }
+
+        #[test]
+        fn interval_set_arithmetic(a in interval_set(), b in interval_set(), point in DOMAIN) {
+            let (points_a, points_b) = (points(a.intervals()), points(b.intervals()));
+
+            prop_assert!(is_normalised(&a));
+            prop_assert_eq!(a.len() as usize, points_a.len());
+            for value in DOMAIN {
+                prop_assert_eq!(a.contains(value), points_a.contains(&value));
+            }
+
+            let results = [
+                (a.union(&b), points_a.union(&points_b).copied().collect::<BTreeSet<_>>()),
+                (a.intersection(&b), points_a.intersection(&points_b).copied().collect()),
+                (a.difference(&b), points_a.difference(&points_b).copied().collect()),
+            ];
+            for (set, expected_points) in results {
+                prop_assert!(is_normalised(&set));
+                prop_assert_eq!(points(set.intervals()), expected_points);
+            }
+
+            let (before, after) = a.split_at(point);
+            prop_assert!(is_normalised(&before) && is_normalised(&after));
+            prop_assert_eq!(
+                points(before.intervals()),
+                points_a.iter().copied().filter(|&value| value < point).collect()
+            );
+            prop_assert_eq!(
+                points(after.intervals()),
+                points_a.iter().copied().filter(|&value| value >= point).collect()
+            );
+
+            let mut inserted = a.clone();
+            for &interval in b.intervals() {
+                inserted.insert(interval);
+            }
+            prop_assert_eq!(inserted, a.union(&b));
+        }
+
+        #[test]
+        fn cuboid_arithmetic(a in cuboid(), b in cuboid(), axis in 0..3_usize, point in DOMAIN) {
+            let (points_a, points_b) = (cuboid_points(&[a]), cuboid_points(&[b]));
+
+            prop_assert_eq!(a.volume(), Some(points_a.len() as u128));
+            prop_assert_eq!(a.overlaps(&b), !points_a.is_disjoint(&points_b));
+            let intersection: Vec<Cuboid<i64, 3>> = a.intersection(&b).into_iter().collect();
+            prop_assert_eq!(
+                cuboid_points(&intersection),
+                points_a.intersection(&points_b).copied().collect()
+            );
+
+            let pieces = a.difference(&b);
+            prop_assert!(pieces.len() <= 6);
+            prop_assert_eq!(
+                cuboid_points(&pieces),
+                points_a.difference(&points_b).copied().collect()
+            );
+            // The pieces are disjoint, so their volumes add up to the points they cover.
+            let volume: u128 = pieces.iter().map(|piece| piece.volume().unwrap_or(0)).sum();
+            prop_assert_eq!(volume, (points_a.len() - points_a.intersection(&points_b).count()) as u128);
+
+            let (before, after) = a.split_at(axis, point);
+            let halves: Vec<Cuboid<i64, 3>> = before.into_iter().chain(after).collect();
+            prop_assert_eq!(cuboid_points(&halves), points_a.clone());
+            if let Some(before) = before {
+                prop_assert!(before.extents[axis].end <= point);
+            }
+            if let Some(after) = after {
+                prop_assert!(after.extents[axis].start >= point);
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use day1::Vocabulary;
+use day2::CubeSet;
+use day7::ExplanationFormat;
+use progress::Progress;
+
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+
+pub mod day25;
+
+pub mod cycle;
+pub mod geometry;
+pub mod graph;
+pub mod grid;
+pub mod interval;
+pub mod math;
+pub mod progress;
+
+/// The answers to both parts of a day's puzzle.
+pub type Answers = (i128, i128);
+
+/// The days that can be solved, in order.
+pub const DAYS: [u32; 10] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 25];
+
+/// One of the two parts of a day's puzzle.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Part {
+    A,
+    B,
+}
+
+/// The puzzle rules that can be changed from their defaults.
+pub struct Settings {
+    /// The words that spell out digits in day 1.
+    pub vocabulary: Vocabulary,
+    /// Whether day 1 skips lines without digits.
+    pub day1_mode: day1::Mode,
+    /// The cubes in the bag that day 2 plays with.
+    pub bag: CubeSet,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            vocabulary: Vocabulary::english(),
+            day1_mode: day1::Mode::Strict,
+            bag: CubeSet::puzzle_bag(),
+        }
+    }
+}
+
+/// Solves both parts of the puzzle of a day for the given puzzle input.
+pub fn solve(
+    day_number: u32,
+    data: &str,
+    settings: &Settings,
+    progress: &mut dyn Progress,
+) -> Result<Answers, Box<dyn std::error::Error>> {
+    match day_number {
+        1 => day1::solution(data, &settings.vocabulary, settings.day1_mode, progress),
+        2 => day2::solution(data, &settings.bag, progress),
+        3 => day3::solution(data, progress),
+        4 => day4::solution(data, progress),
+        5 => day5::solution(data, progress),
+        6 => day6::solution(data, progress),
+        7 => day7::solution(data, progress),
+        8 => day8::solution(data, progress),
+        9 => day9::solution(data, progress),
+        25 => day25::solution(data, progress),
+        _ => Err(format!("day {} has no solution", day_number).into()),
+    }
+}
+
+/// Solves one part of the puzzle of a day, for inputs that only make sense for that part.
+pub fn solve_part(
+    day_number: u32,
+    part: Part,
+    data: &str,
+    settings: &Settings,
+    progress: &mut dyn Progress,
+) -> Result<i128, Box<dyn std::error::Error>> {
+    let answer = match (day_number, part) {
+        (1, Part::A) => day1::puzzle_a(data, settings.day1_mode)?.into(),
+        (1, Part::B) => day1::puzzle_b(data, &settings.vocabulary, settings.day1_mode)?.into(),
+        (2, Part::A) => day2::puzzle_a(data, &settings.bag)?.into(),
+        (2, Part::B) => day2::puzzle_b(data)?.into(),
+        (3, Part::A) => day3::puzzle_a(data)?.into(),
+        (3, Part::B) => day3::puzzle_b(data)?.into(),
+        (4, Part::A) => day4::puzzle_a(data)?.into(),
+        (4, Part::B) => day4::puzzle_b(data)?.into(),
+        (5, Part::A) => day5::puzzle_a(data)?.into(),
+        (5, Part::B) => day5::puzzle_b(data)?.into(),
+        (6, Part::A) => i128::try_from(day6::puzzle_a(data)?)?,
+        (6, Part::B) => i128::try_from(day6::puzzle_b(data)?)?,
+        (7, Part::A) => day7::puzzle_a(data)?.into(),
+        (7, Part::B) => day7::puzzle_b(data)?.into(),
+        (8, Part::A) => day8::puzzle_a(data)?.into(),
+        (8, Part::B) => day8::puzzle_b(data, progress)?.into(),
+        (9, Part::A) => day9::puzzle_a(data)?.into(),
+        (9, Part::B) => day9::puzzle_b(data)?.into(),
+        (25, Part::A) => day25::puzzle_a(data)?.into(),
+        (25, Part::B) => day25::puzzle_b(data)?.into(),
+        _ => return Err(format!("day {} has no solution", day_number).into()),
+    };
+
+    Ok(answer)
+}
+
+/// Explains how the solution of a day came about, for the days that support it.
+pub fn explain(
+    day_number: u32,
+    data: &str,
+    format: &ExplanationFormat,
+) -> Result<String, Box<dyn std::error::Error>> {
+    match day_number {
+        7 => day7::explain(data, format),
+        _ => Err(format!("day {} has no explanation", day_number).into()),
+    }
+}
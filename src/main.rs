@@ -1,33 +1,47 @@
-use std::{fs::File, io::Read};
-
-mod day1;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
+use std::{fs::File, io::Read, time::Instant};
+
+use aoc_2023::day7::ExplanationFormat;
+use aoc_2023::progress::{self, Progress};
+use aoc_2023::Settings;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let solutions = vec![
-        crate::day1::solution(&load_data("day1")?)?,
-        crate::day2::solution(&load_data("day2")?)?,
-        crate::day3::solution(&load_data("day3")?)?,
-        crate::day4::solution(&load_data("day4")?)?,
-        crate::day5::solution(&load_data("day5")?)?,
-        crate::day6::solution(&load_data("day6")?)?,
-        crate::day7::solution(&load_data("day7")?)?,
-        crate::day8::solution(&load_data("day8")?)?,
-        crate::day9::solution(&load_data("day9")?)?,
-    ];
+    let Options {
+        mut progress,
+        explain,
+        settings,
+    } = Options::from_args(std::env::args().skip(1))?;
+
+    if let Some((day_number, format)) = explain {
+        let data = load_data(&format!("day{}", day_number))?;
+        print!("{}", aoc_2023::explain(day_number, &data, &format)?);
+
+        return Ok(());
+    }
+
+    let mut solutions = vec![];
+    for day_number in aoc_2023::DAYS {
+        // Days are only solved once their puzzle input has been added.
+        let data = match load_data(&format!("day{}", day_number)) {
+            Ok(data) => data,
+            Err(_) => {
+                progress.report(&format!("day {}: no puzzle input, skipped", day_number));
+                continue;
+            }
+        };
+
+        let now = Instant::now();
+        let answers = aoc_2023::solve(day_number, &data, &settings, progress.as_mut())?;
+        solutions.push((day_number, answers));
+        progress.report(&format!(
+            "day {}: solved in {}ms",
+            day_number,
+            now.elapsed().as_millis()
+        ));
+    }
 
     let solutions_report: String = solutions
         .iter()
-        .enumerate()
-        .map(|(index, (solution_a, solution_b))| {
-            let day_number = index + 1;
+        .map(|(day_number, (solution_a, solution_b))| {
             format!(
                 "{}A: {}\n{}B: {}\n",
                 day_number, solution_a, day_number, solution_b
@@ -43,6 +57,62 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+struct Options {
+    progress: Box<dyn Progress>,
+    // The day to explain the solution of, instead of reporting all solutions.
+    explain: Option<(u32, ExplanationFormat)>,
+    settings: Settings,
+}
+
+impl Options {
+    /// Solvers are silent, unless `--progress` is passed to report to stderr, or
+    /// `--progress-log PATH` to report to a log file. `--explain DAY` prints how the solution of a
+    /// day came about, as a table or as JSON with `--format json`. `--vocabulary NAME` selects the
+    /// language of the spelled-out digits in day 1: english (the default), dutch or german. Day 1
+    /// fails on lines without digits, unless `--lenient` is passed to skip them. `--bag CUBES`
+    /// sets the cubes in the bag of day 2, such as `"12 red, 13 green, 14 blue"`.
+    fn from_args(
+        mut args: impl Iterator<Item = String>,
+    ) -> Result<Options, Box<dyn std::error::Error>> {
+        let mut progress: Box<dyn Progress> = Box::new(progress::Silent);
+        let mut explain_day = None;
+        let mut format = ExplanationFormat::Table;
+        let mut settings = Settings::default();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--progress" => progress = Box::new(progress::Stderr),
+                "--progress-log" => {
+                    let path = args.next().ok_or("--progress-log needs a path")?;
+                    progress = Box::new(progress::Log::create(&path)?);
+                }
+                "--explain" => {
+                    let day_number = args.next().ok_or("--explain needs a day")?;
+                    explain_day = Some(day_number.parse::<u32>()?);
+                }
+                "--format" => {
+                    format = args.next().ok_or("--format needs a format")?.parse()?;
+                }
+                "--lenient" => settings.day1_mode = aoc_2023::day1::Mode::Lenient,
+                "--bag" => {
+                    settings.bag = args.next().ok_or("--bag needs cubes")?.parse()?;
+                }
+                "--vocabulary" => {
+                    settings.vocabulary =
+                        args.next().ok_or("--vocabulary needs a name")?.parse()?;
+                }
+                _ => return Err(format!("unknown argument {:?}", arg).into()),
+            }
+        }
+
+        Ok(Options {
+            progress,
+            explain: explain_day.map(|day_number| (day_number, format)),
+            settings,
+        })
+    }
+}
+
 fn load_data(file_name: &str) -> Result<String, Box<dyn std::error::Error>> {
     let data_directory = "data/";
     let mut file = File::open(format!("{}{}", data_directory, file_name))?;
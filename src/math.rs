@@ -0,0 +1,279 @@
+pub fn gcd(a: u64, b: u64) -> u64 {
+    let (mut a, mut b) = (a, b);
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+
+    a
+}
+
+/// The least common multiple, or `None` if it does not fit in a u64.
+pub fn lcm(a: u64, b: u64) -> Option<u64> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+/// The greatest common divisor of all numbers, which is 0 for no numbers.
+pub fn gcd_all(numbers: &[u64]) -> u64 {
+    numbers
+        .iter()
+        .fold(0, |divisor, &number| gcd(divisor, number))
+}
+
+/// The least common multiple of all numbers, which is 1 for no numbers, or `None` if it does not
+/// fit in a u64.
+pub fn lcm_all(numbers: &[u64]) -> Option<u64> {
+    numbers
+        .iter()
+        .try_fold(1, |multiple, &number| lcm(multiple, number))
+}
+
+/// Returns `(g, x, y)` such that `a * x + b * y = g = gcd(a, b)`, with `g` never negative, or
+/// `None` if a step does not fit in an i128. That only happens when `a` or `b` is `i128::MIN`.
+pub fn extended_gcd(a: i128, b: i128) -> Option<(i128, i128, i128)> {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1_i128, 0_i128);
+    let (mut old_y, mut y) = (0_i128, 1_i128);
+    let step = |old: i128, new: i128, quotient: i128| old.checked_sub(quotient.checked_mul(new)?);
+
+    while r != 0 {
+        let quotient = old_r.checked_div(r)?;
+        (old_r, r) = (r, step(old_r, r, quotient)?);
+        (old_x, x) = (x, step(old_x, x, quotient)?);
+        (old_y, y) = (y, step(old_y, y, quotient)?);
+    }
+
+    if old_r < 0 {
+        Some((
+            old_r.checked_neg()?,
+            old_x.checked_neg()?,
+            old_y.checked_neg()?,
+        ))
+    } else {
+        Some((old_r, old_x, old_y))
+    }
+}
+
+/// Combines congruences `x = remainder (mod modulus)` into a single congruence modulo the least
+/// common multiple of the moduli, which need not be coprime. Returns `Ok(None)` when the
+/// congruences are inconsistent, and an error when a modulus is 0 or the combined modulus does
+/// not fit in a u64.
+pub fn crt(congruences: &[(u64, u64)]) -> Result<Option<(u64, u64)>, String> {
+    let mut combined = (0, 1);
+
+    for &(remainder, modulus) in congruences {
+        if modulus == 0 {
+            return Err("modulus of a congruence is 0".to_string());
+        }
+
+        match crt_pair(combined, (remainder % modulus, modulus))? {
+            Some(solution) => combined = solution,
+            None => return Ok(None),
+        }
+    }
+
+    Ok(Some(combined))
+}
+
+fn crt_pair((r1, m1): (u64, u64), (r2, m2): (u64, u64)) -> Result<Option<(u64, u64)>, String> {
+    let (g, x, _) = extended_gcd(m1 as i128, m2 as i128).expect("moduli below 2^64 fit in i128");
+    let difference = r2 as i128 - r1 as i128;
+
+    if difference % g != 0 {
+        return Ok(None);
+    }
+
+    let modulus = lcm(m1, m2).ok_or("overflow: combined modulus does not fit in u64")?;
+
+    // Both factors are below 2^64, so their product fits in a u128.
+    let m2_reduced = m2 as i128 / g;
+    let quotient = (difference / g).rem_euclid(m2_reduced) as u128;
+    let inverse = x.rem_euclid(m2_reduced) as u128;
+    let k = quotient * inverse % m2_reduced as u128;
+    let remainder = (r1 as u128 + m1 as u128 * k) % modulus as u128;
+
+    Ok(Some((remainder as u64, modulus)))
+}
+
+/// The largest integer whose square is at most `n`.
+pub fn isqrt_u64(n: u64) -> u64 {
+    isqrt_u128(n as u128) as u64
+}
+
+/// The largest integer whose square is at most `n`. Newton's method from an estimate above the
+/// root descends to it without overshooting, and squares are only compared through checked
+/// multiplication.
+pub fn isqrt_u128(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+
+    // 2^ceil(bits / 2) is at least the root.
+    let bits = u128::BITS - n.leading_zeros();
+    let mut root = 1_u128 << bits.div_ceil(2);
+
+    loop {
+        let next = (root + n / root) / 2;
+        if next >= root {
+            break;
+        }
+        root = next;
+    }
+
+    debug_assert!(root.checked_mul(root).is_some_and(|square| square <= n));
+    debug_assert!((root + 1)
+        .checked_mul(root + 1)
+        .is_none_or(|square| square > n));
+
+    root
+}
+
+/// The square root of `n` if it is a perfect square.
+pub fn exact_sqrt_u64(n: u64) -> Option<u64> {
+    let root = isqrt_u64(n);
+
+    (root * root == n).then_some(root)
+}
+
+/// The square root of `n` if it is a perfect square.
+pub fn exact_sqrt_u128(n: u128) -> Option<u128> {
+    let root = isqrt_u128(n);
+
+    (root * root == n).then_some(root)
+}
+
+/// `base` to the power of `exponent` modulo `modulus`, by repeated squaring. Returns `None` for a
+/// modulus of 0.
+pub fn mod_pow(base: u64, exponent: u64, modulus: u64) -> Option<u64> {
+    if modulus == 0 {
+        return None;
+    }
+
+    // Products of two numbers below the modulus fit in a u128.
+    let modulus = modulus as u128;
+    let mut result = 1 % modulus;
+    let mut base = base as u128 % modulus;
+    let mut exponent = exponent;
+
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result * base % modulus;
+        }
+        base = base * base % modulus;
+        exponent >>= 1;
+    }
+
+    Some(result as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn divisors_and_multiples() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(0, 7), 7);
+        assert_eq!(gcd(0, 0), 0);
+        assert_eq!(lcm(4, 6), Some(12));
+        assert_eq!(lcm(0, 6), Some(0));
+        assert_eq!(lcm(u64::MAX, u64::MAX - 1), None);
+
+        assert_eq!(gcd_all(&[12, 18, 8]), 2);
+        assert_eq!(gcd_all(&[]), 0);
+        assert_eq!(lcm_all(&[2, 3, 4, 5]), Some(60));
+        assert_eq!(lcm_all(&[]), Some(1));
+        assert_eq!(lcm_all(&[1 << 63, 3]), None);
+
+        assert_eq!(extended_gcd(240, 46), Some((2, -9, 47)));
+        assert_eq!(extended_gcd(-4, 6), Some((2, 1, 1)));
+        assert_eq!(extended_gcd(0, 0), Some((0, 1, 0)));
+        assert_eq!(extended_gcd(i128::MAX, i128::MAX - 1), Some((1, 1, -1)));
+        assert_eq!(extended_gcd(i128::MIN, 0), None);
+        assert_eq!(extended_gcd(i128::MIN, 1), None);
+        assert_eq!(extended_gcd(i128::MIN, -1), None);
+    }
+
+    #[test]
+    fn chinese_remainders() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Ok(Some((23, 105))));
+        assert_eq!(crt(&[(1, 4), (3, 6)]), Ok(Some((9, 12))));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), Ok(None));
+        assert_eq!(crt(&[(13, 5)]), Ok(Some((3, 5))));
+        assert_eq!(crt(&[]), Ok(Some((0, 1))));
+        assert!(crt(&[(1, 0)]).is_err());
+        assert!(crt(&[(0, u64::MAX), (0, u64::MAX - 1)]).is_err());
+
+        let large_primes = [(1, 4_294_967_291), (2, 4_294_967_279)];
+        let (remainder, modulus) = crt(&large_primes)
+            .expect("modulus fits in u64")
+            .expect("coprime moduli are consistent");
+        assert_eq!(modulus, 4_294_967_291 * 4_294_967_279);
+        assert_eq!(remainder % 4_294_967_291, 1);
+        assert_eq!(remainder % 4_294_967_279, 2);
+    }
+
+    #[test]
+    fn roots_and_powers() {
+        assert_eq!(isqrt_u64(0), 0);
+        assert_eq!(isqrt_u64(15), 3);
+        assert_eq!(isqrt_u64(16), 4);
+        assert_eq!(isqrt_u64(u64::MAX), u32::MAX as u64);
+        assert_eq!(isqrt_u128(u128::MAX), u64::MAX as u128);
+        assert_eq!(isqrt_u128((1 << 100) - 1), (1 << 50) - 1);
+
+        assert_eq!(exact_sqrt_u64(49), Some(7));
+        assert_eq!(exact_sqrt_u64(50), None);
+        assert_eq!(
+            exact_sqrt_u128(u64::MAX as u128 * u64::MAX as u128),
+            Some(u64::MAX as u128)
+        );
+
+        assert_eq!(mod_pow(4, 13, 497), Some(445));
+        assert_eq!(mod_pow(7, 0, 1), Some(0));
+        assert_eq!(mod_pow(u64::MAX, 2, u64::MAX - 1), Some(1));
+        assert_eq!(mod_pow(2, 10, 0), None);
+    }
+
+    proptest! {
+        #[test]
+        fn crt_matches_brute_force(congruences in prop::collection::vec((0..30_u64, 1..12_u64), 0..4)) {
+            let modulus = lcm_all(&congruences.iter().map(|&(_, modulus)| modulus).collect::<Vec<_>>())
+                .expect("small moduli");
+            let solves = |x: u64| congruences.iter().all(|&(remainder, m)| x % m == remainder % m);
+            let expected = (0..modulus).find(|&x| solves(x)).map(|x| (x, modulus));
+
+            prop_assert_eq!(crt(&congruences), Ok(expected));
+        }
+
+        #[test]
+        fn roots_match_std(n in any::<u128>(), m in any::<u64>()) {
+            prop_assert_eq!(isqrt_u128(n), n.isqrt());
+            prop_assert_eq!(isqrt_u64(m), m.isqrt());
+        }
+
+        #[test]
+        fn mod_pow_matches_repeated_multiplication(base in any::<u64>(), exponent in 0..200_u64, modulus in 1..u64::MAX) {
+            let expected = (0..exponent).fold(1 % modulus as u128, |power, _| {
+                power * (base as u128 % modulus as u128) % modulus as u128
+            });
+
+            prop_assert_eq!(mod_pow(base, exponent, modulus), Some(expected as u64));
+        }
+
+        #[test]
+        fn gcd_divides_and_lcm_is_multiple(a in 1..10_000_u64, b in 1..10_000_u64) {
+            let divisor = gcd(a, b);
+            let multiple = lcm(a, b).expect("small numbers");
+
+            prop_assert!(a % divisor == 0 && b % divisor == 0);
+            prop_assert_eq!(divisor * multiple, a * b);
+            prop_assert_eq!((1..=divisor).rev().find(|d| a % d == 0 && b % d == 0), Some(divisor));
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+
+/// Receives progress messages from solvers. Long-running solvers report through this instead of
+/// printing, so that nothing but the solutions report ends up on stdout.
+pub trait Progress {
+    fn report(&mut self, message: &str);
+}
+
+/// Discards every message. Solvers are silent unless the runner is asked for progress.
+pub struct Silent;
+
+impl Progress for Silent {
+    fn report(&mut self, _message: &str) {}
+}
+
+/// Writes every message to stderr.
+pub struct Stderr;
+
+impl Progress for Stderr {
+    fn report(&mut self, message: &str) {
+        eprintln!("{}", message);
+    }
+}
+
+/// Appends every message to a log file.
+pub struct Log {
+    file: File,
+}
+
+impl Log {
+    /// Opens the log file for appending, creating it if it does not exist yet. Messages of earlier
+    /// runs are kept.
+    pub fn create(path: &str) -> std::io::Result<Log> {
+        let file = OpenOptions::new().append(true).create(true).open(path)?;
+
+        Ok(Log { file })
+    }
+}
+
+impl Progress for Log {
+    fn report(&mut self, message: &str) {
+        // Progress is best effort; a failing log must not fail the solution.
+        let _ = writeln!(self.file, "{}", message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_appends() {
+        let path =
+            std::env::temp_dir().join(format!("aoc_2023_progress_{}.log", std::process::id()));
+        let path = path.to_str().expect("temporary path is valid unicode");
+        let _ = std::fs::remove_file(path);
+
+        Log::create(path).expect("creating log").report("first run");
+        Log::create(path).expect("opening log").report("second run");
+
+        let log = std::fs::read_to_string(path).expect("reading log");
+        std::fs::remove_file(path).expect("removing log");
+        assert_eq!(log, "first run\nsecond run\n");
+    }
+}
@@ -0,0 +1,129 @@
+//! Runs every example under `data/examples`. Each `dayN/NAME.in` holds a puzzle input and the
+//! `NAME.out` next to it the expected answers, one line per part such as `A: 142`. A part without
+//! a line is not checked, for examples that only make sense for the other part.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use libtest_mimic::{Arguments, Failed, Trial};
+
+use aoc_2023::progress::Silent;
+use aoc_2023::{Part, Settings};
+
+fn main() {
+    let arguments = Arguments::from_args();
+    let trials = match find_examples() {
+        Ok(trials) => trials,
+        Err(error) => {
+            eprintln!("could not read the examples: {}", error);
+            std::process::exit(101);
+        }
+    };
+
+    libtest_mimic::run(&arguments, trials).exit();
+}
+
+fn find_examples() -> Result<Vec<Trial>, Box<dyn std::error::Error>> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("data/examples");
+    let mut trials = vec![];
+
+    for day_directory in sorted_entries(&root)? {
+        if !day_directory.is_dir() {
+            continue;
+        }
+        let day_name = day_directory
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| format!("{} has no name", day_directory.display()))?
+            .to_string();
+        let day_number: u32 = day_name
+            .strip_prefix("day")
+            .and_then(|number| number.parse().ok())
+            .ok_or_else(|| format!("{} is not named dayN", day_directory.display()))?;
+
+        for input_path in sorted_entries(&day_directory)? {
+            if input_path
+                .extension()
+                .is_none_or(|extension| extension != "in")
+            {
+                continue;
+            }
+            let example_name = input_path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .ok_or_else(|| format!("{} has no name", input_path.display()))?
+                .to_string();
+
+            let output_path = input_path.with_extension("out");
+            let expected = fs::read_to_string(&output_path)
+                .map_err(|error| format!("reading {}: {}", output_path.display(), error))?;
+
+            for (part, answer) in parse_answers(&expected)
+                .map_err(|error| format!("{}: {}", output_path.display(), error))?
+            {
+                let part_name = match part {
+                    Part::A => "a",
+                    Part::B => "b",
+                };
+                let name = format!("{}::{}::{}", day_name, example_name, part_name);
+                let input_path = input_path.clone();
+                trials.push(Trial::test(name, move || {
+                    check_example(day_number, part, &input_path, answer)
+                }));
+            }
+        }
+    }
+
+    Ok(trials)
+}
+
+fn check_example(
+    day_number: u32,
+    part: Part,
+    input_path: &Path,
+    expected: i128,
+) -> Result<(), Failed> {
+    let data = fs::read_to_string(input_path)
+        .map_err(|error| format!("reading {}: {}", input_path.display(), error))?;
+    let answer = aoc_2023::solve_part(day_number, part, &data, &Settings::default(), &mut Silent)
+        .map_err(|error| format!("solving {}: {}", input_path.display(), error))?;
+
+    if answer != expected {
+        return Err(format!("expected {}, got {}", expected, answer).into());
+    }
+
+    Ok(())
+}
+
+/// Reads lines like `A: 142` or `B: -3` into the expected answer of each part.
+fn parse_answers(expected: &str) -> Result<Vec<(Part, i128)>, String> {
+    expected
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (part, answer) = line
+                .split_once(':')
+                .ok_or_else(|| format!("line {:?} is not like `A: 142`", line))?;
+            let part = match part.trim() {
+                "A" => Part::A,
+                "B" => Part::B,
+                other => return Err(format!("unknown part {:?}", other)),
+            };
+            let answer = answer
+                .trim()
+                .parse()
+                .map_err(|error| format!("answer of line {:?}: {}", line, error))?;
+
+            Ok((part, answer))
+        })
+        .collect()
+}
+
+fn sorted_entries(directory: &Path) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let mut paths = fs::read_dir(directory)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    paths.sort();
+
+    Ok(paths)
+}